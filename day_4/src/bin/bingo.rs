@@ -0,0 +1,283 @@
+//! An interactive Bingo caller. Loads the drawn values and boards from a file in the puzzle's input
+//! format, then draws the values one at a time (each time Enter is pressed, or automatically with a
+//! delay), re-rendering every board and announcing the winners as it goes.
+//!
+//! ```text
+//! bingo <input-file> [--auto <ms>] [--per-row <n>] [--session <file>]
+//! ```
+//!
+//! When a session file is provided, the game is saved to it after every draw and resumed from it the
+//! next time the caller starts. In interactive mode, `u` undoes the last draw and `q` quits.
+
+use std::{
+    env, fs,
+    io::{stdin, stdout, BufRead, ErrorKind, IsTerminal, Write},
+    process, thread,
+    time::Duration,
+};
+
+use day_4::{parse_bingo, BingoGame, DrawRecord, GameEvent, ParseOptions, Renderer};
+
+/// The command line options
+struct Options {
+    input: String,
+    auto: Option<Duration>,
+    per_row: usize,
+    session: Option<String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Options {
+            input: String::new(),
+            auto: None,
+            per_row: 5,
+            session: None,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| format!("{} expects a value", name))
+            };
+            match arg.as_str() {
+                "--auto" => {
+                    let ms = value(arg)?
+                        .parse::<u64>()
+                        .map_err(|_| "--auto expects a number of milliseconds".to_string())?;
+                    options.auto = Some(Duration::from_millis(ms));
+                }
+                "--per-row" => {
+                    options.per_row = value(arg)?
+                        .parse::<usize>()
+                        .map_err(|_| "--per-row expects a number".to_string())?;
+                }
+                "--session" => options.session = Some(value(arg)?),
+                _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+                _ => options.input = arg.clone(),
+            }
+        }
+        if options.input.is_empty() {
+            return Err("missing the input file".to_string());
+        }
+        Ok(options)
+    }
+}
+
+/// A saved game, which is the input file it was loaded from and the values drawn so far
+#[derive(Debug, PartialEq, Eq)]
+struct Session {
+    input: String,
+    draws: Vec<usize>,
+}
+
+impl Session {
+    fn to_text(&self) -> String {
+        let draws = self
+            .draws
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        format!("input={}\ndraws={}\n", self.input, draws)
+    }
+
+    fn from_text(text: &str) -> Result<Self, String> {
+        let mut session = Session {
+            input: String::new(),
+            draws: vec![],
+        };
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            match line.split_once('=') {
+                Some(("input", input)) => session.input = input.to_string(),
+                Some(("draws", draws)) => {
+                    session.draws = draws
+                        .split(',')
+                        .filter(|v| !v.trim().is_empty())
+                        .map(|v| v.trim().parse::<usize>())
+                        .collect::<Result<Vec<usize>, _>>()
+                        .map_err(|e| format!("invalid draws in session: {}", e))?;
+                }
+                _ => return Err(format!("unexpected line in session: {}", line)),
+            }
+        }
+        Ok(session)
+    }
+}
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    if let Err(e) = run(&args) {
+        eprintln!("bingo: {}", e);
+        eprintln!("usage: bingo <input-file> [--auto <ms>] [--per-row <n>] [--session <file>]");
+        process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut options = Options::parse(args)?;
+
+    // Resume the session if there is one, which also decides which input is being played
+    let session = match &options.session {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(text) => Some(Session::from_text(&text)?),
+            // Only a missing file starts a new session, anything else would be overwritten by `save`
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("can't read session {}: {}", path, e)),
+        },
+        None => None,
+    };
+    if let Some(session) = &session {
+        options.input = session.input.clone();
+    }
+
+    let input = fs::read_to_string(&options.input)
+        .map_err(|e| format!("can't read {}: {}", options.input, e))?;
+    let (values, boards) =
+        parse_bingo(&input, &ParseOptions::default()).map_err(|e| e.to_string())?;
+    let mut game = BingoGame::new(boards);
+    let draws = session.map(|s| s.draws).unwrap_or_default();
+    check_draws(&draws, &values)?;
+    for value in draws {
+        game.draw(value);
+    }
+
+    let renderer = Renderer::detect();
+    let mut announcements = vec![];
+    let mut lines = stdin().lock().lines();
+    loop {
+        render(&renderer, &game, &options, &announcements);
+        if game.is_finished() || game.round() >= values.len() {
+            println!("Game over after {} draws", game.round());
+            return Ok(());
+        }
+
+        match options.auto {
+            Some(delay) => thread::sleep(delay),
+            None => {
+                print!("Press Enter to draw, u to undo, q to quit: ");
+                stdout().flush().map_err(|e| e.to_string())?;
+                match lines.next() {
+                    Some(Ok(line)) if line.trim() == "q" => return Ok(()),
+                    Some(Ok(line)) if line.trim() == "u" => {
+                        if let Some(record) = game.undo() {
+                            announcements = vec![format!("Undid the draw of {}", record.value)];
+                        }
+                        save(&game, &options)?;
+                        continue;
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(e.to_string()),
+                    None => return Ok(()),
+                }
+            }
+        }
+
+        let record = game.draw(values[game.round()]);
+        announcements = announce(&record);
+        save(&game, &options)?;
+    }
+}
+
+/// Checks that the draws of a saved session are the first values of the input, which they won't be
+/// when the input file has changed since the session was saved
+fn check_draws(draws: &[usize], values: &[usize]) -> Result<(), String> {
+    if values.starts_with(draws) {
+        return Ok(());
+    }
+    let round = draws
+        .iter()
+        .zip(values)
+        .position(|(d, v)| d != v)
+        .unwrap_or(values.len());
+    Err(format!(
+        "session doesn't match the input, draw #{} was {} but the input has {}",
+        round + 1,
+        draws[round],
+        values
+            .get(round)
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "no more values".to_string())
+    ))
+}
+
+/// Returns a line describing the draw followed by a line for each board that won on it
+fn announce(record: &DrawRecord) -> Vec<String> {
+    let mut lines = vec![format!("Draw #{}: {}", record.round + 1, record.value)];
+    for event in record.events.iter() {
+        if let GameEvent::Won { board, score, .. } = event {
+            lines.push(format!(
+                "BINGO! Board {} wins with a score of {}",
+                board, score
+            ));
+        }
+    }
+    lines
+}
+
+fn render(renderer: &Renderer, game: &BingoGame, options: &Options, announcements: &[String]) {
+    if stdout().is_terminal() {
+        // Clear the screen and move the cursor to the top left
+        print!("\x1b[2J\x1b[H");
+    }
+    println!(
+        "{}\n",
+        renderer.render_boards(game.boards(), options.per_row)
+    );
+    let winners = game
+        .finishing_order()
+        .iter()
+        .map(|f| format!("{} ({})", f.board, f.score))
+        .collect::<Vec<String>>();
+    if !winners.is_empty() {
+        println!("Winners so far: {}", winners.join(", "));
+    }
+    announcements.iter().for_each(|a| println!("{}", a));
+}
+
+fn save(game: &BingoGame, options: &Options) -> Result<(), String> {
+    if let Some(path) = &options.session {
+        let session = Session {
+            input: options.input.clone(),
+            draws: game.draws(),
+        };
+        fs::write(path, session.to_text()).map_err(|e| format!("can't save {}: {}", path, e))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_session_round_trip() {
+    let session = Session {
+        input: "src/input.txt".to_string(),
+        draws: vec![7, 4, 9],
+    };
+    assert_eq!("input=src/input.txt\ndraws=7,4,9\n", session.to_text());
+    assert_eq!(session, Session::from_text(&session.to_text()).unwrap());
+    assert!(Session::from_text("draws=1,x").is_err());
+}
+
+#[test]
+fn test_options_parse() {
+    let args = ["input.txt", "--auto", "250", "--session", "game.txt"].map(String::from);
+    let options = Options::parse(&args).unwrap();
+    assert_eq!("input.txt", options.input);
+    assert_eq!(Some(Duration::from_millis(250)), options.auto);
+    assert_eq!(Some("game.txt".to_string()), options.session);
+    assert!(Options::parse(&["--auto".to_string()]).is_err());
+}
+
+#[test]
+fn test_check_draws() {
+    assert!(check_draws(&[], &[7, 4, 9]).is_ok());
+    assert!(check_draws(&[7, 4], &[7, 4, 9]).is_ok());
+    assert_eq!(
+        "session doesn't match the input, draw #2 was 5 but the input has 4",
+        check_draws(&[7, 5], &[7, 4, 9]).unwrap_err()
+    );
+    assert_eq!(
+        "session doesn't match the input, draw #4 was 1 but the input has no more values",
+        check_draws(&[7, 4, 9, 1], &[7, 4, 9]).unwrap_err()
+    );
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use crate::Board;
+
+/// The masks for the five rows followed by the five columns, using the same bit layout as
+/// [`crate::WinPattern`] where the bit index of a cell is `row * 5 + col`.
+const WIN_MASKS: [u32; 10] = win_masks();
+
+const fn win_masks() -> [u32; 10] {
+    let mut masks = [0; 10];
+    let mut i = 0;
+    while i < 5 {
+        masks[i] = 0b11111 << (i * 5);
+        masks[5 + i] = 0b00001_00001_00001_00001_00001 << i;
+        i += 1;
+    }
+    masks
+}
+
+/// A compact alternative to [`Board`] where the visited state of the whole board is a single `u32`
+/// mark mask. Marking a value sets a bit for each cell that contains it, checking for a bingo is a
+/// handful of AND/compare operations against the precomputed row and column masks, and the sum of
+/// the unmarked cells is kept as a running total so that scoring doesn't need to walk the grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BitBoard {
+    // The cell values in row-major order
+    values: [usize; 25],
+    // Bit `row * 5 + col` is set when that cell has been marked
+    marked: u32,
+    // The running total of all the cells that haven't been marked yet
+    unmarked_sum: usize,
+}
+
+impl BitBoard {
+    /// Marks every cell containing the value and returns true if the board has a bingo as a result.
+    /// The cells are compared against the value in a single pass which the compiler can vectorize.
+    pub fn mark(&mut self, value: usize) -> bool {
+        let hits = self
+            .values
+            .iter()
+            .enumerate()
+            .fold(0u32, |m, (idx, v)| m | (((*v == value) as u32) << idx));
+        self.mark_bits(value, hits)
+    }
+
+    /// Marks the cells of the bits, which all contain the value, and returns true if the board has a
+    /// bingo as a result. Bits that are already marked are left alone.
+    fn mark_bits(&mut self, value: usize, bits: u32) -> bool {
+        let new = bits & !self.marked;
+        if new == 0 {
+            return false;
+        }
+        self.marked |= new;
+        self.unmarked_sum -= value * new.count_ones() as usize;
+        self.has_bingo()
+    }
+
+    /// Returns true if any row or column has been completely marked
+    pub fn has_bingo(&self) -> bool {
+        WIN_MASKS.iter().any(|m| self.marked & m == *m)
+    }
+
+    /// Returns the sum of all the cells that haven't been marked yet
+    pub fn sum_unmarked(&self) -> usize {
+        self.unmarked_sum
+    }
+
+    /// Returns the mask of marked cells
+    pub fn marked(&self) -> u32 {
+        self.marked
+    }
+}
+
+impl From<&Board> for BitBoard {
+    /// Converts the board, including any cells that have already been visited
+    fn from(board: &Board) -> Self {
+        let mut values = [0; 25];
+        board
+            .grid
+            .iter()
+            .flatten()
+            .enumerate()
+            .for_each(|(idx, c)| values[idx] = c.value);
+        let marked = board.visited_mask();
+        let unmarked_sum = values
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| marked & 1 << idx == 0)
+            .map(|(_, v)| v)
+            .sum();
+        Self {
+            values,
+            marked,
+            unmarked_sum,
+        }
+    }
+}
+
+/// Values above this limit are indexed with a [`HashMap`] rather than a dense array of offsets
+const DENSE_LIMIT: usize = 1 << 16;
+
+/// Maps each value to the (board, bit) pairs of the cells that contain it. When the values are small
+/// (as they are in the puzzle) the entries are stored contiguously and grouped by value, with an offset
+/// table indexed by the value itself, so that a lookup is two array reads.
+enum BitIndex {
+    Dense {
+        offsets: Vec<usize>,
+        entries: Vec<(usize, u32)>,
+    },
+    Sparse(HashMap<usize, Vec<(usize, u32)>>),
+}
+
+impl BitIndex {
+    fn new(boards: &[BitBoard]) -> Self {
+        let max = boards.iter().flat_map(|b| b.values).max().unwrap_or(0);
+        if max >= DENSE_LIMIT {
+            let mut entries: HashMap<usize, Vec<(usize, u32)>> = HashMap::new();
+            for (board, b) in boards.iter().enumerate() {
+                for (bit, v) in b.values.iter().enumerate() {
+                    entries.entry(*v).or_default().push((board, 1 << bit));
+                }
+            }
+            return BitIndex::Sparse(entries);
+        }
+
+        // Count the cells for each value, turn the counts into offsets and then fill in the entries
+        let mut offsets = vec![0; max + 2];
+        boards
+            .iter()
+            .flat_map(|b| b.values)
+            .for_each(|v| offsets[v + 1] += 1);
+        for v in 1..offsets.len() {
+            offsets[v] += offsets[v - 1];
+        }
+        let mut next = offsets.clone();
+        let mut entries = vec![(0, 0); boards.len() * 25];
+        for (board, b) in boards.iter().enumerate() {
+            for (bit, v) in b.values.iter().enumerate() {
+                entries[next[*v]] = (board, 1 << bit);
+                next[*v] += 1;
+            }
+        }
+        BitIndex::Dense { offsets, entries }
+    }
+
+    fn get(&self, value: usize) -> &[(usize, u32)] {
+        match self {
+            BitIndex::Dense { offsets, entries } if value + 1 < offsets.len() => {
+                &entries[offsets[value]..offsets[value + 1]]
+            }
+            BitIndex::Dense { .. } => &[],
+            BitIndex::Sparse(entries) => entries.get(&value).map(Vec::as_slice).unwrap_or(&[]),
+        }
+    }
+}
+
+/// Evaluates the bit boards and returns the score for the winning and loosing boards, just like
+/// [`crate::evaluate_bingo_boards`]. The boards are copied, so the same boards can be evaluated again.
+/// The draws are processed round by round and each drawn value only sets the bits of the cells that
+/// contain it. Boards that win on the same draw are tied and, like
+/// [`crate::evaluate_bingo_boards`], the first tied board in input order is used for both the winning
+/// and loosing board.
+pub fn evaluate_bingo_bitboards(values: &[usize], boards: &[BitBoard]) -> (usize, usize) {
+    let mut boards = boards.to_vec();
+    let index = BitIndex::new(&boards);
+    let mut won = vec![false; boards.len()];
+    let mut remaining = boards.len();
+    let mut winning = None;
+    let mut loosing = None;
+
+    for (round, value) in values.iter().enumerate() {
+        // A board can hold the value more than once, so the bits of all its cells are marked
+        // together before it's checked for a bingo and scored
+        for cells in index.get(*value).chunk_by(|a, b| a.0 == b.0) {
+            let idx = cells[0].0;
+            let bits = cells.iter().fold(0, |m, (_, bit)| m | bit);
+            if won[idx] || !boards[idx].mark_bits(*value, bits) {
+                continue;
+            }
+            won[idx] = true;
+            remaining -= 1;
+            let score = value * boards[idx].sum_unmarked();
+            if winning.is_none() {
+                winning = Some(score);
+            }
+            if loosing.is_none_or(|(r, _)| r != round) {
+                loosing = Some((round, score));
+            }
+        }
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    (
+        winning.expect("expected a winning board"),
+        loosing.expect("expected a loosing board").1,
+    )
+}
+
+#[test]
+fn test_bitboard_mark() {
+    let (_, boards) = crate::get_example_game();
+    let mut board = BitBoard::from(&boards[0]);
+    assert_eq!(300, board.sum_unmarked());
+    for v in [8, 2, 23, 4] {
+        assert!(!board.mark(v));
+    }
+    assert_eq!(0b01111_00000, board.marked());
+    assert!(board.mark(24));
+    assert_eq!(239, board.sum_unmarked());
+    // Marking the same value twice doesn't change the total
+    board.mark(24);
+    assert_eq!(239, board.sum_unmarked());
+}
+
+#[test]
+fn test_bitboard_from_visited_board() {
+    let (_, mut boards) = crate::get_example_game();
+    boards[0].visit_value(22);
+    let board = BitBoard::from(&boards[0]);
+    assert_eq!(1, board.marked());
+    assert_eq!(278, board.sum_unmarked());
+}
+
+#[test]
+fn test_evaluate_bingo_bitboards() {
+    let (values, boards) = crate::get_example_game();
+    let boards = boards.iter().map(BitBoard::from).collect::<Vec<BitBoard>>();
+    assert_eq!((4512, 1924), evaluate_bingo_bitboards(&values, &boards));
+    let (values, boards) = crate::parse_input();
+    let boards = boards.iter().map(BitBoard::from).collect::<Vec<BitBoard>>();
+    assert_eq!((8136, 12738), evaluate_bingo_bitboards(&values, &boards));
+}
+
+#[test]
+fn test_evaluate_bingo_bitboards_duplicate_value() {
+    // The first board wins on the 5 that fills its top row, and the 5 in the bottom corner must
+    // not count towards the unmarked sum
+    let (values, boards) = crate::get_duplicate_value_game();
+    let bitboards = boards.iter().map(BitBoard::from).collect::<Vec<BitBoard>>();
+    assert_eq!(
+        crate::evaluate_bingo_boards(values.clone(), boards),
+        evaluate_bingo_bitboards(&values, &bitboards)
+    );
+    assert_eq!(
+        5 * (550 - 46 - 48),
+        evaluate_bingo_bitboards(&values, &bitboards).0
+    );
+}
+
+#[test]
+fn test_evaluate_bingo_bitboards_sparse_values() {
+    // Shifting every value past the dense limit forces the sparse index
+    let (values, boards) = crate::get_example_game();
+    let values = values
+        .into_iter()
+        .map(|v| v + DENSE_LIMIT)
+        .collect::<Vec<usize>>();
+    let boards = boards
+        .iter()
+        .map(|b| {
+            let mut shifted = Board::default();
+            for (row, cells) in b.grid.iter().enumerate() {
+                for (col, c) in cells.iter().enumerate() {
+                    shifted.set_cell_idx(row, col, c.value + DENSE_LIMIT);
+                }
+            }
+            shifted
+        })
+        .collect::<Vec<Board>>();
+    let bitboards = boards.iter().map(BitBoard::from).collect::<Vec<BitBoard>>();
+    assert!(matches!(BitIndex::new(&bitboards), BitIndex::Sparse(_)));
+    assert_eq!(
+        crate::evaluate_bingo_boards_indexed(values.clone(), boards),
+        evaluate_bingo_bitboards(&values, &bitboards)
+    );
+}
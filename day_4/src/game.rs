@@ -0,0 +1,510 @@
+use std::{fmt, sync::Arc};
+
+use crate::{Board, ValueIndex, WinPattern};
+
+/// Something that happened to a single board while processing a draw
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    /// The board visited the cells at the zero-based row/col indexes because they contained the drawn value
+    Marked {
+        board: usize,
+        cells: Vec<(usize, usize)>,
+    },
+    /// The board completed one of the game's win patterns on this draw
+    Won {
+        board: usize,
+        pattern: WinPattern,
+        score: usize,
+    },
+}
+
+/// Everything that happened during a single draw of the game
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DrawRecord {
+    /// The zero-based index of the draw
+    pub round: usize,
+    /// The value that was drawn
+    pub value: usize,
+    /// The events in board order, a board's [`GameEvent::Marked`] always comes before its [`GameEvent::Won`]
+    pub events: Vec<GameEvent>,
+}
+
+/// Records when a board won the game and what it scored
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finish {
+    pub board: usize,
+    pub round: usize,
+    pub value: usize,
+    pub score: usize,
+}
+
+/// The outcome of a complete game
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameSummary {
+    /// One record for each draw that was processed
+    pub log: Vec<DrawRecord>,
+    /// Every board that won, in the order they won. Boards that won on the same draw are in input order.
+    pub finishing_order: Vec<Finish>,
+    /// The indexes of the boards that never won
+    pub never_won: Vec<usize>,
+}
+
+/// The record of every draw of a game, kept as a list where each draw points back at the draws that
+/// came before it. Copies of the history share those earlier draws rather than copying them, so a
+/// snapshot only costs as much as a pointer no matter how many draws were made.
+#[derive(Clone, Default)]
+struct History(Option<Arc<Entry>>);
+
+struct Entry {
+    record: DrawRecord,
+    // The number of draws up to and including this one
+    len: usize,
+    previous: History,
+}
+
+impl History {
+    fn len(&self) -> usize {
+        self.0.as_ref().map_or(0, |e| e.len)
+    }
+
+    fn push(&mut self, record: DrawRecord) {
+        let previous = std::mem::take(self);
+        self.0 = Some(Arc::new(Entry {
+            record,
+            len: previous.len() + 1,
+            previous,
+        }));
+    }
+
+    /// Removes the most recent draw, which is only copied when another history still shares it
+    fn pop(&mut self) -> Option<DrawRecord> {
+        let entry = self.0.take()?;
+        match Arc::try_unwrap(entry) {
+            Ok(mut entry) => {
+                *self = std::mem::take(&mut entry.previous);
+                Some(entry.record)
+            }
+            Err(entry) => {
+                *self = entry.previous.clone();
+                Some(entry.record.clone())
+            }
+        }
+    }
+
+    /// Returns the draws from the most recent one back to the first
+    fn iter(&self) -> impl Iterator<Item = &DrawRecord> {
+        std::iter::successors(self.0.as_deref(), |e| e.previous.0.as_deref()).map(|e| &e.record)
+    }
+
+    /// Returns a copy of the draws in the order they were made
+    fn to_vec(&self) -> Vec<DrawRecord> {
+        let mut records = self.iter().cloned().collect::<Vec<DrawRecord>>();
+        records.reverse();
+        records
+    }
+}
+
+impl Drop for History {
+    /// Drops the draws one at a time, where the default would recurse once per draw
+    fn drop(&mut self) {
+        let mut next = self.0.take();
+        while let Some(entry) = next {
+            next = Arc::try_unwrap(entry)
+                .ok()
+                .and_then(|mut e| e.previous.0.take());
+        }
+    }
+}
+
+impl PartialEq for History {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for History {}
+
+impl fmt::Debug for History {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.to_vec()).finish()
+    }
+}
+
+/// Plays a game of Bingo across all boards at the same time. Unlike [`crate::evaluate_bingo_boards`]
+/// which runs each board through all the values on its own, the game processes the values one draw
+/// at a time so that we know what every board was doing on every draw. Boards stop being marked once
+/// they have won.
+///
+/// Every draw is kept on an undo stack, so draws can be undone, the game can be rewound to an earlier
+/// draw and replayed with a different value, and snapshots can be taken and restored.
+#[derive(Clone)]
+pub struct BingoGame {
+    boards: Vec<Board>,
+    // Lets each draw go straight to the boards containing the drawn value
+    index: ValueIndex,
+    patterns: Vec<WinPattern>,
+    // Tracks whether each board has already won so that it is not marked or scored again
+    won: Vec<bool>,
+    finishing_order: Vec<Finish>,
+    // The record of every draw so far, which doubles as the undo stack
+    history: History,
+}
+
+/// A copy of the state of a [`BingoGame`] at a point in time. Rather than copying the boards along with
+/// their lookup tables, only the visited cells of each board are kept as a single `u32` mask. The
+/// record of every draw is shared with the game rather than copied, so a snapshot grows with the
+/// number of boards but not with the number of draws made.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameSnapshot {
+    marks: Vec<u32>,
+    won: Vec<bool>,
+    finishing_order: Vec<Finish>,
+    history: History,
+}
+
+impl GameSnapshot {
+    /// Returns the number of draws that had been made when the snapshot was taken
+    pub fn round(&self) -> usize {
+        self.history.len()
+    }
+}
+
+impl BingoGame {
+    /// Creates a new game that uses the standard row and column patterns
+    pub fn new(boards: Vec<Board>) -> Self {
+        Self::with_patterns(boards, WinPattern::standard())
+    }
+
+    /// Creates a new game where a board wins when it completes any of the provided patterns
+    pub fn with_patterns(boards: Vec<Board>, patterns: Vec<WinPattern>) -> Self {
+        Self {
+            won: vec![false; boards.len()],
+            index: ValueIndex::new(&boards),
+            boards,
+            patterns,
+            finishing_order: vec![],
+            history: History::default(),
+        }
+    }
+
+    /// Draws a single value, marking it on every board that has not won yet, and returns the events
+    /// that happened as a result.
+    pub fn draw(&mut self, value: usize) -> DrawRecord {
+        let round = self.round();
+        let mut events = vec![];
+        // The index entries are in board order, so each chunk contains the cells of a single board
+        for entries in self.index.get(value).chunk_by(|a, b| a.0 == b.0) {
+            let idx = entries[0].0;
+            let board = &mut self.boards[idx];
+            if self.won[idx] {
+                continue;
+            }
+            let cells = entries
+                .iter()
+                .filter(|(_, row, col)| !board.grid[*row][*col].visited)
+                .map(|(_, row, col)| (*row, *col))
+                .collect::<Vec<(usize, usize)>>();
+            if cells.is_empty() {
+                continue;
+            }
+            for (row, col) in cells.iter() {
+                board.visit_cell_idx(*row, *col);
+            }
+            events.push(GameEvent::Marked { board: idx, cells });
+            if let Some((pattern, _)) = board.winning_pattern(&self.patterns) {
+                let score = value * board.sum_unvisited();
+                events.push(GameEvent::Won {
+                    board: idx,
+                    pattern: pattern.clone(),
+                    score,
+                });
+                self.finishing_order.push(Finish {
+                    board: idx,
+                    round,
+                    value,
+                    score,
+                });
+                self.won[idx] = true;
+            }
+        }
+        let record = DrawRecord {
+            round,
+            value,
+            events,
+        };
+        self.history.push(record.clone());
+        record
+    }
+
+    /// Draws all the provided values in order and returns a summary of the game, including any draws
+    /// that were made before. The game stops early once every board has won.
+    pub fn play(mut self, values: &[usize]) -> GameSummary {
+        self.draw_all(values);
+        self.summary()
+    }
+
+    /// Draws the values in order until every board has won
+    fn draw_all(&mut self, values: &[usize]) {
+        for value in values {
+            if self.is_finished() {
+                break;
+            }
+            self.draw(*value);
+        }
+    }
+
+    /// Returns a summary of the game so far
+    pub fn summary(&self) -> GameSummary {
+        GameSummary {
+            log: self.history.to_vec(),
+            finishing_order: self.finishing_order.clone(),
+            never_won: self.never_won(),
+        }
+    }
+
+    /// Returns the number of draws made so far
+    pub fn round(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Returns the values drawn so far, in order
+    pub fn draws(&self) -> Vec<usize> {
+        let mut draws = self.history.iter().map(|r| r.value).collect::<Vec<usize>>();
+        draws.reverse();
+        draws
+    }
+
+    /// Reverts the most recent draw and returns its record, or `None` if nothing has been drawn. The
+    /// cells it marked are unvisited and the boards that won on it are back in the game.
+    pub fn undo(&mut self) -> Option<DrawRecord> {
+        let record = self.history.pop()?;
+        for event in record.events.iter() {
+            match event {
+                GameEvent::Marked { board, cells } => cells
+                    .iter()
+                    .for_each(|(row, col)| self.boards[*board].unvisit_cell_idx(*row, *col)),
+                GameEvent::Won { board, .. } => {
+                    self.won[*board] = false;
+                    self.finishing_order.pop();
+                }
+            }
+        }
+        Some(record)
+    }
+
+    /// Undoes draws until only the first `round` draws remain
+    pub fn rewind(&mut self, round: usize) {
+        while self.round() > round {
+            self.undo();
+        }
+    }
+
+    /// Takes a snapshot of the current state of the game
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            marks: self.boards.iter().map(Board::visited_mask).collect(),
+            won: self.won.clone(),
+            finishing_order: self.finishing_order.clone(),
+            history: self.history.clone(),
+        }
+    }
+
+    /// Restores the game to the state it was in when the snapshot was taken. The snapshot must have been
+    /// taken from this game (or a clone of it), otherwise this will panic.
+    pub fn restore(&mut self, snapshot: &GameSnapshot) {
+        assert_eq!(
+            self.boards.len(),
+            snapshot.marks.len(),
+            "snapshot was taken from a different game"
+        );
+        self.boards
+            .iter_mut()
+            .zip(snapshot.marks.iter())
+            .for_each(|(b, m)| b.set_visited_mask(*m));
+        self.won = snapshot.won.clone();
+        self.finishing_order = snapshot.finishing_order.clone();
+        self.history = snapshot.history.clone();
+    }
+
+    /// Re-runs the game as if `value` had been drawn at draw `round` instead of the value that actually
+    /// was, followed by the rest of the original draws, and returns the summary of that alternative game.
+    /// The game itself is left untouched. Panics if `round` is past the draws made so far.
+    pub fn replay(&self, round: usize, value: usize) -> GameSummary {
+        assert!(
+            round <= self.round(),
+            "can't replay a draw that hasn't happened"
+        );
+        let draws = self.draws();
+        let mut game = self.clone();
+        game.rewind(round);
+        game.draw(value);
+        game.draw_all(draws.get(round + 1..).unwrap_or(&[]));
+        game.summary()
+    }
+
+    /// Returns true once every board has won
+    pub fn is_finished(&self) -> bool {
+        self.won.iter().all(|w| *w)
+    }
+
+    /// Returns the boards that are being played
+    pub fn boards(&self) -> &[Board] {
+        &self.boards
+    }
+
+    /// Returns the boards that have won so far, in the order they won
+    pub fn finishing_order(&self) -> &[Finish] {
+        &self.finishing_order
+    }
+
+    /// Returns the indexes of the boards that have not won yet
+    pub fn never_won(&self) -> Vec<usize> {
+        (0..self.boards.len()).filter(|b| !self.won[*b]).collect()
+    }
+}
+
+#[test]
+fn test_bingo_game_example() {
+    let (values, boards) = crate::get_example_game();
+    let summary = BingoGame::new(boards).play(&values);
+    let order = summary
+        .finishing_order
+        .iter()
+        .map(|f| (f.board, f.score))
+        .collect::<Vec<(usize, usize)>>();
+    assert_eq!(vec![(2, 4512), (0, 2192), (1, 1924)], order);
+    assert!(summary.never_won.is_empty());
+    // The game stops on the draw that completes the last board
+    assert_eq!(15, summary.log.len());
+}
+
+#[test]
+fn test_bingo_game_events() {
+    let (values, boards) = crate::get_example_game();
+    let mut game = BingoGame::new(boards);
+    let record = game.draw(values[0]);
+    assert_eq!(
+        vec![
+            GameEvent::Marked {
+                board: 0,
+                cells: vec![(2, 4)]
+            },
+            GameEvent::Marked {
+                board: 1,
+                cells: vec![(2, 2)]
+            },
+            GameEvent::Marked {
+                board: 2,
+                cells: vec![(4, 4)]
+            },
+        ],
+        record.events
+    );
+    for v in &values[1..12] {
+        game.draw(*v);
+    }
+    assert_eq!(vec![0, 1], game.never_won());
+    assert_eq!(1, game.finishing_order().len());
+}
+
+#[test]
+fn test_bingo_game_never_won() {
+    let (values, boards) = crate::get_example_game();
+    let summary = BingoGame::new(boards).play(&values[..13]);
+    assert_eq!(1, summary.finishing_order.len());
+    assert_eq!(vec![0, 1], summary.never_won);
+}
+
+#[test]
+fn test_bingo_game_undo() {
+    let (values, boards) = crate::get_example_game();
+    let mut game = BingoGame::new(boards);
+    let before = game.clone().play(&values[..11]);
+    for v in &values[..12] {
+        game.draw(*v);
+    }
+    assert_eq!(1, game.finishing_order().len());
+    let undone = game.undo().unwrap();
+    assert_eq!(24, undone.value);
+    assert!(game.finishing_order().is_empty());
+    assert_eq!(before, game.summary());
+    assert_eq!(
+        before.log.len(),
+        game.boards()[2].visited_mask().count_ones() as usize
+    );
+    game.rewind(0);
+    assert!(game.boards().iter().all(|b| b.visited_mask() == 0));
+    assert!(game.undo().is_none());
+}
+
+#[test]
+fn test_bingo_game_snapshot() {
+    let (values, boards) = crate::get_example_game();
+    let mut game = BingoGame::new(boards);
+    for v in &values[..10] {
+        game.draw(*v);
+    }
+    let snapshot = game.snapshot();
+    assert_eq!(10, snapshot.round());
+    let expected = game.clone().play(&values[10..]);
+    game.draw_all(&values[10..]);
+    game.rewind(3);
+    game.restore(&snapshot);
+    assert_eq!(10, game.round());
+    assert_eq!(expected, game.play(&values[10..]));
+}
+
+#[test]
+fn test_bingo_game_snapshot_shares_history() {
+    let (values, boards) = crate::get_example_game();
+    let mut game = BingoGame::new(boards);
+    game.draw_all(&values[..10]);
+    let snapshot = game.snapshot();
+    let shared =
+        |a: &History, b: &History| Arc::ptr_eq(a.0.as_ref().unwrap(), b.0.as_ref().unwrap());
+    assert!(shared(&game.history, &snapshot.history));
+
+    // Undoing a shared draw leaves the snapshot's copy as it was
+    let undone = game.undo().unwrap();
+    assert_eq!(undone, *snapshot.history.iter().next().unwrap());
+    assert_eq!((9, 10), (game.round(), snapshot.round()));
+
+    // Far more draws than the stack could drop one inside the other
+    for _ in 0..200_000 {
+        game.draw(1000);
+    }
+    assert_eq!(values[..9], game.draws()[..9]);
+    drop(game);
+}
+
+#[test]
+fn test_bingo_game_replay() {
+    let (values, boards) = crate::get_example_game();
+    let mut game = BingoGame::new(boards);
+    game.draw_all(&values);
+    let original = game.summary();
+    // Drawing 25 rather than 24 on draw 11 means the third board never completes its top row, so the
+    // first board wins instead
+    let replayed = game.replay(11, 25);
+    assert_eq!(original, game.summary());
+    assert_eq!(original.log[..11], replayed.log[..11]);
+    assert_eq!(25, replayed.log[11].value);
+    assert_eq!(
+        (0, 13),
+        (
+            replayed.finishing_order[0].board,
+            replayed.finishing_order[0].round
+        )
+    );
+    assert_eq!(vec![2], replayed.never_won);
+    // Replaying with the original value reproduces the original game
+    assert_eq!(original, game.replay(11, 24));
+}
+
+#[test]
+fn test_bingo_game_input() {
+    let (values, boards) = crate::parse_input();
+    let summary = BingoGame::new(boards).play(&values);
+    assert_eq!(8136, summary.finishing_order.first().unwrap().score);
+    assert_eq!(12738, summary.finishing_order.last().unwrap().score);
+}
@@ -0,0 +1,202 @@
+use std::{error::Error, fmt, ops::Range};
+
+use rand::{
+    seq::{index, SliceRandom},
+    Rng, SeedableRng,
+};
+use rand_chacha::ChaCha8Rng;
+
+/// Describes the puzzle inputs to generate
+#[derive(Clone, Debug)]
+pub struct GeneratorConfig {
+    /// The number of boards to generate
+    pub boards: usize,
+    /// The range the board numbers are picked from. Every number in the range is drawn once.
+    pub range: Range<usize>,
+    /// The seed for the generator, the same config always generates the same input
+    pub seed: u64,
+    /// When set, guarantees that at least one board wins within this many draws
+    pub win_within: Option<usize>,
+}
+
+impl Default for GeneratorConfig {
+    /// Matches the shape of the puzzle input: 100 boards numbered from 0 to 99
+    fn default() -> Self {
+        Self {
+            boards: 100,
+            range: 0..100,
+            seed: 0,
+            win_within: None,
+        }
+    }
+}
+
+/// Describes why an input could not be generated
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GenerateError {
+    /// The range has fewer than the 25 numbers needed to fill a board without repeats
+    RangeTooSmall(usize),
+    /// A board needs at least 5 draws to win, so the guarantee can't be met
+    WinWithinTooSmall(usize),
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::RangeTooSmall(n) => {
+                write!(f, "range has {} numbers, a board needs at least 25", n)
+            }
+            GenerateError::WinWithinTooSmall(k) => {
+                write!(
+                    f,
+                    "a board can't win within {} draws, at least 5 are needed",
+                    k
+                )
+            }
+        }
+    }
+}
+
+impl Error for GenerateError {}
+
+/// Generates a random puzzle input in the same text format as `input.txt`, so it can be read with
+/// [`crate::parse_bingo`]. Each board contains unique numbers picked from the configured range and the
+/// draws are a shuffle of the entire range.
+///
+/// ## Example
+/// ```
+/// # use day_4::{generate_bingo, parse_bingo, GeneratorConfig, ParseOptions};
+/// let config = GeneratorConfig { boards: 3, seed: 7, ..GeneratorConfig::default() };
+/// let input = generate_bingo(&config).unwrap();
+/// let (values, boards) = parse_bingo(&input, &ParseOptions::default()).unwrap();
+/// assert_eq!((100, 3), (values.len(), boards.len()));
+/// ```
+pub fn generate_bingo(config: &GeneratorConfig) -> Result<String, GenerateError> {
+    let size = config.range.len();
+    if size < 25 {
+        return Err(GenerateError::RangeTooSmall(size));
+    }
+    if let Some(k) = config.win_within.filter(|k| *k < 5) {
+        return Err(GenerateError::WinWithinTooSmall(k));
+    }
+
+    let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+    let boards = (0..config.boards)
+        .map(|_| {
+            index::sample(&mut rng, size, 25)
+                .into_iter()
+                .map(|i| config.range.start + i)
+                .collect::<Vec<usize>>()
+        })
+        .collect::<Vec<Vec<usize>>>();
+    let mut values = config.range.clone().collect::<Vec<usize>>();
+    values.shuffle(&mut rng);
+
+    // Move the values of a random row or column of a random board into the first k draws
+    if let (Some(k), false) = (config.win_within, boards.is_empty()) {
+        let board = &boards[rng.gen_range(0..boards.len())];
+        let line = rng.gen_range(0..10);
+        let cells = (0..5).map(|i| {
+            if line < 5 {
+                board[line * 5 + i]
+            } else {
+                board[i * 5 + line - 5]
+            }
+        });
+        let targets = index::sample(&mut rng, k.min(values.len()), 5);
+        for (value, target) in cells.zip(targets) {
+            let current = values.iter().position(|v| *v == value).unwrap();
+            values.swap(current, target);
+        }
+    }
+
+    // Right-align the numbers like the puzzle input does
+    let width = config.range.end.saturating_sub(1).to_string().len();
+    let mut output = values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>()
+        .join(",");
+    output.push('\n');
+    for board in boards {
+        output.push('\n');
+        for row in board.chunks(5) {
+            let row = row
+                .iter()
+                .map(|v| format!("{:>width$}", v, width = width))
+                .collect::<Vec<String>>();
+            output.push_str(&row.join(" "));
+            output.push('\n');
+        }
+    }
+    Ok(output)
+}
+
+#[test]
+fn test_generate_bingo_reproducible() {
+    let config = GeneratorConfig {
+        boards: 20,
+        seed: 3,
+        ..GeneratorConfig::default()
+    };
+    let a = generate_bingo(&config).unwrap();
+    assert_eq!(a, generate_bingo(&config).unwrap());
+    let other = GeneratorConfig { seed: 4, ..config };
+    assert_ne!(a, generate_bingo(&other).unwrap());
+}
+
+#[test]
+fn test_generate_bingo_parses() {
+    let config = GeneratorConfig {
+        boards: 50,
+        range: 1000..1200,
+        seed: 11,
+        win_within: None,
+    };
+    let input = generate_bingo(&config).unwrap();
+    let options = crate::ParseOptions {
+        reject_duplicates: true,
+    };
+    let (values, boards) = crate::parse_bingo(&input, &options).unwrap();
+    assert_eq!(50, boards.len());
+    let mut sorted = values.clone();
+    sorted.sort_unstable();
+    assert_eq!((1000..1200).collect::<Vec<usize>>(), sorted);
+    assert!(input.starts_with(&format!("{},", values[0])));
+}
+
+#[test]
+fn test_generate_bingo_win_within() {
+    for seed in 0..20 {
+        let config = GeneratorConfig {
+            boards: 10,
+            seed,
+            win_within: Some(7),
+            ..GeneratorConfig::default()
+        };
+        let input = generate_bingo(&config).unwrap();
+        let (values, boards) = crate::parse_bingo(&input, &Default::default()).unwrap();
+        let summary = crate::BingoGame::new(boards).play(&values);
+        assert!(summary.finishing_order[0].round < 7);
+    }
+}
+
+#[test]
+fn test_generate_bingo_errors() {
+    let config = GeneratorConfig {
+        range: 0..24,
+        ..GeneratorConfig::default()
+    };
+    assert_eq!(
+        Err(GenerateError::RangeTooSmall(24)),
+        generate_bingo(&config)
+    );
+    let config = GeneratorConfig {
+        win_within: Some(4),
+        ..GeneratorConfig::default()
+    };
+    assert_eq!(
+        Err(GenerateError::WinWithinTooSmall(4)),
+        generate_bingo(&config)
+    );
+}
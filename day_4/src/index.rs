@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use crate::Board;
+
+/// A game-wide index from each value to every (board, row, col) entry that contains it. Where each
+/// [`Board`] only knows about its own cells, this index lets a draw jump straight to the boards that
+/// contain the value instead of asking every board whether it has it. Entries for a value are stored
+/// in board order and then row-major order within the board.
+#[derive(Clone)]
+pub struct ValueIndex {
+    entries: HashMap<usize, Vec<(usize, usize, usize)>>,
+}
+
+impl ValueIndex {
+    /// Builds the index from the current cells of the provided boards
+    pub fn new(boards: &[Board]) -> Self {
+        let mut entries: HashMap<usize, Vec<(usize, usize, usize)>> = HashMap::new();
+        for (board, b) in boards.iter().enumerate() {
+            for (row, cells) in b.grid.iter().enumerate() {
+                for (col, cell) in cells.iter().enumerate() {
+                    entries
+                        .entry(cell.value)
+                        .or_default()
+                        .push((board, row, col));
+                }
+            }
+        }
+        Self { entries }
+    }
+
+    /// Returns all the (board, row, col) entries that contain the value
+    pub fn get(&self, value: usize) -> &[(usize, usize, usize)] {
+        self.entries.get(&value).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+/// Evaluates the bingo boards round by round and returns the score for the winning and loosing bingo
+/// boards, just like [`crate::evaluate_bingo_boards`]. Rather than running each board through every
+/// value, each draw uses a [`ValueIndex`] to touch only the boards that contain the drawn value, which
+/// scales much better with thousands of boards. Boards that win on the same draw are tied and, like
+/// [`crate::evaluate_bingo_boards`], the first tied board in input order is used for both the winning
+/// and loosing board.
+pub fn evaluate_bingo_boards_indexed(values: Vec<usize>, mut boards: Vec<Board>) -> (usize, usize) {
+    let index = ValueIndex::new(&boards);
+    let mut won = vec![false; boards.len()];
+    let mut remaining = boards.len();
+    let mut winning = None;
+    let mut loosing = None;
+
+    for (round, value) in values.iter().enumerate() {
+        // A board can hold the value more than once, so all of its cells are visited before it's
+        // checked for a bingo and scored
+        for cells in index.get(*value).chunk_by(|a, b| a.0 == b.0) {
+            let board = cells[0].0;
+            if won[board] {
+                continue;
+            }
+            let b = &mut boards[board];
+            for (_, row, col) in cells {
+                if !b.grid[*row][*col].visited {
+                    b.visit_cell_idx(*row, *col);
+                }
+            }
+            if b.has_bingo() {
+                won[board] = true;
+                remaining -= 1;
+                let score = value * b.sum_unvisited();
+                if winning.is_none() {
+                    winning = Some(score);
+                }
+                if loosing.is_none_or(|(r, _)| r != round) {
+                    loosing = Some((round, score));
+                }
+            }
+        }
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    (
+        winning.expect("expected a winning board"),
+        loosing.expect("expected a loosing board").1,
+    )
+}
+
+#[test]
+fn test_value_index() {
+    let (_, boards) = crate::get_example_game();
+    let index = ValueIndex::new(&boards);
+    assert_eq!(&[(0, 2, 4), (1, 2, 2), (2, 4, 4)], index.get(7));
+    assert!(index.get(99).is_empty());
+}
+
+#[test]
+fn test_evaluate_bingo_boards_indexed() {
+    let (values, boards) = crate::get_example_game();
+    assert_eq!((4512, 1924), evaluate_bingo_boards_indexed(values, boards));
+    let (values, boards) = crate::parse_input();
+    assert_eq!((8136, 12738), evaluate_bingo_boards_indexed(values, boards));
+}
+
+#[test]
+fn test_evaluate_bingo_boards_indexed_duplicate_value() {
+    // The first board wins on the 5 that fills its top row, and the 5 in the bottom corner must
+    // not count towards the unvisited sum
+    let (values, boards) = crate::get_duplicate_value_game();
+    let scores = (5 * (550 - 46 - 48), 54 * (1550 - 260));
+    assert_eq!(
+        scores,
+        evaluate_bingo_boards_indexed(values.clone(), boards.clone())
+    );
+    assert_eq!(scores, crate::evaluate_bingo_boards(values, boards));
+}
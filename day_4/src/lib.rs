@@ -2,53 +2,55 @@
 
 use std::{collections::HashMap, vec};
 
+mod bitboard;
+mod game;
+mod generator;
+mod index;
+mod montecarlo;
+mod parse;
+mod pattern;
+mod render;
+mod results;
+mod solver;
+
+pub use bitboard::{evaluate_bingo_bitboards, BitBoard};
+pub use game::{BingoGame, DrawRecord, Finish, GameEvent, GameSnapshot, GameSummary};
+pub use generator::{generate_bingo, GenerateError, GeneratorConfig};
+pub use index::{evaluate_bingo_boards_indexed, ValueIndex};
+pub use montecarlo::{estimate_win_probabilities, Estimate, SimulationConfig, WinProbability};
+pub use parse::{parse_bingo, BoardParseError, BoardParseErrorKind, ParseOptions};
+pub use pattern::WinPattern;
+pub use render::Renderer;
+pub use results::{evaluate_bingo_boards_with_policy, BingoResults, BoardScore, TiePolicy};
+pub use solver::{find_draw_order, Goal, SearchOutcome, SolverConfig};
+
 /// Evaluates the bingo boards and returns the score for the winning and loosing bingo boards. This
-/// accomplishes both part #1 and part #2 of this problem.
+/// accomplishes both part #1 and part #2 of this problem. When several boards win on the same draw,
+/// the first of them in input order is used, see [`evaluate_bingo_boards_with_policy`] to get the
+/// other tied boards.
 pub fn evaluate_bingo_boards(values: Vec<usize>, boards: Vec<Board>) -> (usize, usize) {
-    let mut winning_board = None;
-    let mut winning_board_round = 0;
-    let mut winning_board_num = 0;
-    let mut loosing_board = None;
-    let mut loosing_board_round = 0;
-    let mut loosing_board_num = 0;
+    let results = evaluate_bingo_boards_with_policy(&values, boards, TiePolicy::First);
 
-    // Evaluate the Bingo values for each board in order, keeping track of which
-    // one wins first. We update the variables declared above if we find a winning
-    // board that beats the existing winning board.
-    'boards: for mut board in boards {
-        for (round, value) in values.iter().enumerate() {
-            if board.visit_value(*value) {
-                // Determine both the winning and the loosing boards by comparing the
-                // round that the boards won on. If the round is lower than the current
-                // winning boards number of rounds to win, then this board becomes the
-                // new winning board. If this round is higher than the current loosing
-                // board, then this becomes the new loosing board.
-                if winning_board.is_none() || round <= winning_board_round {
-                    winning_board_num = *value;
-                    winning_board = Some(board.clone());
-                    winning_board_round = round;
-                } else if round > loosing_board_round {
-                    loosing_board_num = *value;
-                    loosing_board = Some(board.clone());
-                    loosing_board_round = round;
-                }
-                continue 'boards;
-            }
-        }
-    }
-
-    // Get the sum of all un-visited cells on the board and take that times the value
-    // that won the winning board's round.
+    // The score is the sum of all un-visited cells on the board times the value that
+    // won the board's round.
     (
-        winning_board_num * winning_board.unwrap().sum_unvisited(),
-        loosing_board_num * loosing_board.unwrap().sum_unvisited(),
+        results
+            .winners
+            .first()
+            .expect("expected a winning board")
+            .score,
+        results
+            .losers
+            .first()
+            .expect("expected a loosing board")
+            .score,
     )
 }
 
 /// Contains a value on the Bingo board and provides a wrapper around a value that can
 /// maintain the state of the cell, such as whether the cell has been visited or not.
 /// This state is needed to know whether there has been a bingo.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct Cell {
     visited: bool,
     value: usize,
@@ -70,7 +72,21 @@ impl Cell {
 }
 
 /// A 5x5 Bingo board of usize values. The struct stores a 2d matrix where the first
-/// dimension is the rows and the second dimension is the columns.
+/// dimension is the rows and the second dimension is the columns. Boards are parsed from
+/// a grid-like text representation like the following, see [`parse_bingo`] for the errors
+/// that can be reported.
+///
+/// ## Example
+/// ```
+/// # use day_4::Board;
+/// let board = "22 13 17 11  0
+///    8  2 23  4 24
+///   21  9 14 16  7
+///    6 10  3 18  5
+///    1 12 20 15 19"
+///     .parse::<Board>()
+///     .unwrap();
+/// ```
 #[derive(Clone)]
 pub struct Board {
     // Contains the actual 5x5 grid
@@ -88,7 +104,7 @@ pub struct Board {
 impl Board {
     /// Creates a new [`Board`] from a given reader. The reader should contain a grid-like
     /// text representation of the 5x5 board like the following. Improperly formatting inputs
-    /// will panic.
+    /// will panic, parse the board with [`str::parse`] instead to get a [`BoardParseError`].
     ///
     /// ## Example
     /// ```
@@ -101,17 +117,9 @@ impl Board {
     ///    1 12 20 15 19"
     /// );
     /// ```
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(input: &str) -> Self {
-        let mut board = Self::default();
-        input.lines().into_iter().enumerate().for_each(|(row, l)| {
-            l.split_whitespace()
-                .filter(|v| v.len() > 0)
-                .into_iter()
-                .map(|v| v.parse::<usize>().expect("expected usize value"))
-                .enumerate()
-                .for_each(|(col, v)| board.set_cell_idx(row, col, v));
-        });
-        board
+        input.parse().expect("expected a valid board")
     }
 
     /// Sets the value at the specified row and column index. Index is zero-based so row 0, col 0 is
@@ -121,16 +129,7 @@ impl Board {
         self.grid[row][col] = Cell::new(val);
 
         // Update the lookup with the new value
-        match self.lookup.get(&val) {
-            Some(v) => {
-                let mut v = v.clone();
-                v.push((row, col));
-                self.lookup.insert(val, v);
-            }
-            None => {
-                self.lookup.insert(val, vec![(row, col)]);
-            }
-        };
+        self.lookup.entry(val).or_default().push((row, col));
     }
 
     /// Gets the value at the specified row and column. The rows and columns are not zero-based so row 1, col 1
@@ -153,18 +152,49 @@ impl Board {
         self.visited_count_by_row[row_idx] += 1;
     }
 
+    /// Reverts [`Board::visit_cell_idx`] for a cell that has been visited, using zero-based indexes.
+    fn unvisit_cell_idx(&mut self, row_idx: usize, col_idx: usize) {
+        self.grid[row_idx][col_idx].visited = false;
+        self.visited_count_by_col[col_idx] -= 1;
+        self.visited_count_by_row[row_idx] -= 1;
+    }
+
+    /// Replaces the visited state of every cell with the provided mask, which uses the same bit layout
+    /// as [`WinPattern`].
+    fn set_visited_mask(&mut self, mask: u32) {
+        self.visited_count_by_row = [0; 5];
+        self.visited_count_by_col = [0; 5];
+        for row in 0..5 {
+            for col in 0..5 {
+                self.grid[row][col].visited = false;
+                if mask & 1 << (row * 5 + col) != 0 {
+                    self.visit_cell_idx(row, col);
+                }
+            }
+        }
+    }
+
     /// Marks any matching values on the Bingo board as 'visited' and returns a boolean representing whether the
     /// board has a bingo.
     fn visit_value(&mut self, val: usize) -> bool {
-        match self.lookup.get(&val) {
-            Some(v) => {
-                for (row, col) in v.clone() {
-                    self.visit_cell_idx(row, col)
-                }
-                self.has_bingo()
-            }
-            None => false,
+        !self.mark_value(val).is_empty() && self.has_bingo()
+    }
+
+    /// Marks any matching values on the Bingo board as 'visited' and returns the zero-based row/col indexes
+    /// of the cells that were newly visited. Cells that were already visited are not visited twice.
+    fn mark_value(&mut self, val: usize) -> Vec<(usize, usize)> {
+        let cells = match self.lookup.get(&val) {
+            Some(v) => v
+                .iter()
+                .filter(|(row, col)| !self.grid[*row][*col].visited)
+                .copied()
+                .collect::<Vec<(usize, usize)>>(),
+            None => return vec![],
+        };
+        for (row, col) in cells.iter() {
+            self.visit_cell_idx(*row, *col)
         }
+        cells
     }
 
     /// Returns true if the board currently has a vertical or horizontal bingo. A bingo is defined by any row or
@@ -205,30 +235,9 @@ impl Default for Board {
 
 /// Parses the input.txt file and returns a vector of Bingo values and a vector of Bingo boards
 /// that were parsed from the file. This function could potentially fail and should only be used
-/// in controlled environments such as tests.
+/// in controlled environments such as tests, use [`parse_bingo`] to handle errors.
 pub fn parse_input() -> (Vec<usize>, Vec<Board>) {
-    let input = include_str!("input.txt");
-
-    // Parse the line containing the values
-    let value_input = input
-        .lines()
-        .next()
-        .expect("expected first line to contain values");
-    let values = value_input
-        .split(",")
-        .map(|v| v.parse::<usize>().expect("expected usize"))
-        .collect::<Vec<usize>>();
-
-    // Parse the lines containing the boards
-    (
-        values,
-        input
-            .split("\n\n")
-            .skip(1)
-            .into_iter()
-            .map(|v| Board::from_str(v))
-            .collect::<Vec<Board>>(),
-    )
+    parse_bingo(include_str!("input.txt"), &ParseOptions::default()).expect("expected valid input")
 }
 
 #[cfg(test)]
@@ -242,6 +251,35 @@ fn get_test_board() -> Board {
     )
 }
 
+/// Returns the example values and boards from the problem description. The first board to win is the
+/// third board (score 4512) and the last board to win is the second board (score 1924).
+#[cfg(test)]
+fn get_example_game() -> (Vec<usize>, Vec<Board>) {
+    let values = vec![
+        7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22, 18, 20, 8, 19, 3,
+        26, 1,
+    ];
+    let boards = [
+        "22 13 17 11  0\n 8  2 23  4 24\n21  9 14 16  7\n 6 10  3 18  5\n 1 12 20 15 19",
+        " 3 15  0  2 22\n 9 18 13 17  5\n19  8  7 25 23\n20 11 10 24  4\n14 21 16 12  6",
+        "14 21 17 24  4\n10 16 15  9 19\n18  8 23 26 20\n22 11 13  6  5\n 2  0 12  3  7",
+    ];
+    (values, boards.iter().map(|b| b.parse().unwrap()).collect())
+}
+
+/// Returns two boards and the values to draw for them, where the first board has a 5 in its top
+/// right and bottom right corners. It wins on the 5 that fills its top row, and its score is
+/// 5 * (550 - 46 - 48). The second board wins on the last value with a score of 54 * (1550 - 260).
+#[cfg(test)]
+fn get_duplicate_value_game() -> (Vec<usize>, Vec<Board>) {
+    let values = vec![10, 11, 12, 13, 5, 50, 51, 52, 53, 54];
+    let boards = [
+        "10 11 12 13  5\n15 16 17 18 19\n20 21 22 23 24\n25 26 27 28 29\n30 31 32 33  5",
+        "50 51 52 53 54\n55 56 57 58 59\n60 61 62 63 64\n65 66 67 68 69\n70 71 72 73 74",
+    ];
+    (values, boards.iter().map(|b| b.parse().unwrap()).collect())
+}
+
 #[test]
 fn test_board_from_str() {
     let board = get_test_board();
@@ -259,25 +297,25 @@ fn test_board_visit_value() {
 #[test]
 fn test_board_has_bingo_horizontal() {
     let mut board = get_test_board();
-    assert_eq!(false, board.has_bingo());
+    assert!(!board.has_bingo());
     board.visit_value(8);
     board.visit_value(2);
     board.visit_value(23);
     board.visit_value(4);
     board.visit_value(24);
-    assert_eq!(true, board.has_bingo());
+    assert!(board.has_bingo());
 }
 
 #[test]
 fn test_board_has_bingo_vertical() {
     let mut board = get_test_board();
-    assert_eq!(false, board.has_bingo());
+    assert!(!board.has_bingo());
     board.visit_value(11);
     board.visit_value(4);
     board.visit_value(16);
     board.visit_value(18);
     board.visit_value(15);
-    assert_eq!(true, board.has_bingo());
+    assert!(board.has_bingo());
 }
 
 #[test]
@@ -0,0 +1,162 @@
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+
+use crate::{evaluate_bingo_boards_with_policy, Board, TiePolicy};
+
+/// The z-score used for the 95% confidence intervals
+const Z_95: f64 = 1.96;
+
+/// Controls how many games are simulated and how they are shuffled
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+    /// The number of random orderings of the remaining values to simulate
+    pub simulations: usize,
+    /// The seed for the random orderings. The same seed always produces the same estimates, regardless
+    /// of how many threads the simulations are spread across.
+    pub seed: u64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            simulations: 10_000,
+            seed: 0,
+        }
+    }
+}
+
+/// An estimated probability along with its 95% confidence interval (a Wilson score interval, which
+/// behaves well even when the probability is close to 0 or 1).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    pub probability: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl Estimate {
+    fn from_counts(successes: usize, trials: usize) -> Self {
+        if trials == 0 {
+            return Self {
+                probability: 0.0,
+                lower: 0.0,
+                upper: 1.0,
+            };
+        }
+        let n = trials as f64;
+        let p = successes as f64 / n;
+        let z2 = Z_95 * Z_95;
+        let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let margin = Z_95 / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+        Self {
+            probability: p,
+            lower: (center - margin).max(0.0),
+            upper: (center + margin).min(1.0),
+        }
+    }
+}
+
+/// The estimated chances of a single board
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WinProbability {
+    /// The zero-based index of the board
+    pub board: usize,
+    /// The probability that the board is the first to win
+    pub first: Estimate,
+    /// The probability that the board is the last to win
+    pub last: Estimate,
+}
+
+/// Estimates each board's probability of winning first and of finishing last, given the values that
+/// have already been drawn and the values that are still left to draw. Each simulation shuffles the
+/// remaining values and evaluates the boards with [`evaluate_bingo_boards_with_policy`], so the boards
+/// are marked exactly as they are by [`crate::evaluate_bingo_boards`]. Boards that tie on a draw all
+/// count as having won first (or finished last), so the probabilities can add up to more than one.
+///
+/// The simulations run in parallel. Each simulation gets its own stream of a seeded ChaCha generator
+/// which keeps the results reproducible.
+pub fn estimate_win_probabilities(
+    boards: &[Board],
+    drawn: &[usize],
+    remaining: &[usize],
+    config: &SimulationConfig,
+) -> Vec<WinProbability> {
+    let (first, last) = (0..config.simulations)
+        .into_par_iter()
+        .fold(
+            || (vec![0; boards.len()], vec![0; boards.len()]),
+            |(mut first, mut last), simulation| {
+                let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+                rng.set_stream(simulation as u64);
+                let mut values = drawn.to_vec();
+                let mut shuffled = remaining.to_vec();
+                shuffled.shuffle(&mut rng);
+                values.extend(shuffled);
+
+                let results =
+                    evaluate_bingo_boards_with_policy(&values, boards.to_vec(), TiePolicy::All);
+                results.winners.iter().for_each(|s| first[s.board] += 1);
+                results.losers.iter().for_each(|s| last[s.board] += 1);
+                (first, last)
+            },
+        )
+        .reduce(
+            || (vec![0; boards.len()], vec![0; boards.len()]),
+            |(mut first, mut last), (f, l)| {
+                first.iter_mut().zip(f).for_each(|(a, b)| *a += b);
+                last.iter_mut().zip(l).for_each(|(a, b)| *a += b);
+                (first, last)
+            },
+        );
+
+    (0..boards.len())
+        .map(|board| WinProbability {
+            board,
+            first: Estimate::from_counts(first[board], config.simulations),
+            last: Estimate::from_counts(last[board], config.simulations),
+        })
+        .collect()
+}
+
+#[test]
+fn test_estimate_win_probabilities_reproducible() {
+    let (values, boards) = crate::get_example_game();
+    let config = SimulationConfig {
+        simulations: 500,
+        seed: 42,
+    };
+    let a = estimate_win_probabilities(&boards, &values[..8], &values[8..], &config);
+    let b = estimate_win_probabilities(&boards, &values[..8], &values[8..], &config);
+    assert_eq!(a, b);
+    for p in a.iter() {
+        assert!(p.first.lower <= p.first.probability && p.first.probability <= p.first.upper);
+        assert!(p.last.lower <= p.last.probability && p.last.probability <= p.last.upper);
+    }
+    // Somebody always wins first and somebody always finishes last
+    assert!(a.iter().map(|p| p.first.probability).sum::<f64>() >= 1.0);
+    assert!(a.iter().map(|p| p.last.probability).sum::<f64>() >= 1.0);
+}
+
+#[test]
+fn test_estimate_win_probabilities_decided() {
+    // The third board has already won after the first 12 values were drawn
+    let (values, boards) = crate::get_example_game();
+    let config = SimulationConfig {
+        simulations: 200,
+        seed: 7,
+    };
+    let estimates = estimate_win_probabilities(&boards, &values[..12], &values[12..], &config);
+    assert_eq!(1.0, estimates[2].first.probability);
+    assert_eq!(0.0, estimates[0].first.probability);
+    assert_eq!(0.0, estimates[2].last.probability);
+    assert!(estimates[2].first.lower > 0.95);
+}
+
+#[test]
+fn test_estimate_from_counts() {
+    let e = Estimate::from_counts(50, 100);
+    assert_eq!(0.5, e.probability);
+    assert!((e.lower - 0.404).abs() < 0.001);
+    assert!((e.upper - 0.596).abs() < 0.001);
+}
@@ -0,0 +1,321 @@
+use std::{collections::HashSet, error::Error, fmt, str::FromStr};
+
+use crate::Board;
+
+/// Describes what went wrong while parsing the Bingo input
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BoardParseErrorKind {
+    /// The input doesn't contain the line of drawn values
+    MissingValues,
+    /// A token isn't a valid non-negative number
+    InvalidNumber(String),
+    /// A row contains more than five numbers
+    TooManyColumns,
+    /// A row contains fewer than five numbers, holds the number that were found
+    TooFewColumns(usize),
+    /// A board contains more than five rows
+    TooManyRows,
+    /// A board contains fewer than five rows, holds the number that were found
+    TooFewRows(usize),
+    /// The number appears more than once on the same board, only reported when
+    /// [`ParseOptions::reject_duplicates`] is set
+    DuplicateNumber(usize),
+}
+
+/// An error produced while parsing Bingo boards. The line and column are one-based and point at the
+/// offending token (or the end of the offending line) in the text that was parsed. The board is the
+/// zero-based index of the board being parsed, or `None` when the error is in the drawn values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoardParseError {
+    pub board: Option<usize>,
+    pub line: usize,
+    pub column: usize,
+    pub kind: BoardParseErrorKind,
+}
+
+impl fmt::Display for BoardParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            BoardParseErrorKind::MissingValues => write!(f, "missing the line of drawn values")?,
+            BoardParseErrorKind::InvalidNumber(t) => write!(f, "invalid number '{}'", t)?,
+            BoardParseErrorKind::TooManyColumns => write!(f, "row has more than 5 numbers")?,
+            BoardParseErrorKind::TooFewColumns(n) => {
+                write!(f, "row has {} numbers, expected 5", n)?
+            }
+            BoardParseErrorKind::TooManyRows => write!(f, "board has more than 5 rows")?,
+            BoardParseErrorKind::TooFewRows(n) => write!(f, "board has {} rows, expected 5", n)?,
+            BoardParseErrorKind::DuplicateNumber(v) => write!(f, "duplicate number {}", v)?,
+        }
+        if let Some(board) = self.board {
+            write!(f, " in board {}", board)?;
+        }
+        write!(f, " at line {}, column {}", self.line, self.column)
+    }
+}
+
+impl Error for BoardParseError {}
+
+/// Controls how strict the parser is
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    /// Rejects any board that contains the same number more than once
+    pub reject_duplicates: bool,
+}
+
+/// A single non-blank line of input along with its one-based line number
+type NumberedLine<'a> = (usize, &'a str);
+
+/// Returns the one-based column of a byte offset into the line, counting characters rather than bytes
+fn column(line: &str, offset: usize) -> usize {
+    line[..offset].chars().count() + 1
+}
+
+/// Returns the whitespace separated tokens of a line along with their one-based columns
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace().map(move |t| {
+        (
+            column(line, t.as_ptr() as usize - line.as_ptr() as usize),
+            t,
+        )
+    })
+}
+
+/// Parses a single board from its (up to) five numbered lines
+fn parse_board(
+    lines: &[NumberedLine],
+    board_idx: Option<usize>,
+    options: &ParseOptions,
+) -> Result<Board, BoardParseError> {
+    let error = |line: usize, column: usize, kind: BoardParseErrorKind| BoardParseError {
+        board: board_idx,
+        line,
+        column,
+        kind,
+    };
+
+    let mut board = Board::default();
+    let mut seen = HashSet::new();
+    for (row, (line_no, line)) in lines.iter().enumerate() {
+        if row == 5 {
+            return Err(error(*line_no, 1, BoardParseErrorKind::TooManyRows));
+        }
+        let mut count = 0;
+        for (col, (column, token)) in tokens(line).enumerate() {
+            if col == 5 {
+                return Err(error(*line_no, column, BoardParseErrorKind::TooManyColumns));
+            }
+            let value = token.parse::<usize>().map_err(|_| {
+                error(
+                    *line_no,
+                    column,
+                    BoardParseErrorKind::InvalidNumber(token.to_string()),
+                )
+            })?;
+            if options.reject_duplicates && !seen.insert(value) {
+                return Err(error(
+                    *line_no,
+                    column,
+                    BoardParseErrorKind::DuplicateNumber(value),
+                ));
+            }
+            board.set_cell_idx(row, col, value);
+            count += 1;
+        }
+        if count < 5 {
+            return Err(error(
+                *line_no,
+                column(line, line.len()),
+                BoardParseErrorKind::TooFewColumns(count),
+            ));
+        }
+    }
+    if lines.len() < 5 {
+        let (line_no, line) = lines.last().copied().unwrap_or((1, ""));
+        return Err(error(
+            line_no,
+            column(line, line.len()),
+            BoardParseErrorKind::TooFewRows(lines.len()),
+        ));
+    }
+    Ok(board)
+}
+
+/// Returns the lines of the input with their one-based line numbers. CRLF line endings are
+/// handled by [`str::lines`].
+fn numbered_lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input.lines().enumerate().map(|(idx, l)| (idx + 1, l))
+}
+
+impl FromStr for Board {
+    type Err = BoardParseError;
+
+    /// Parses a single 5x5 board, see [`Board`]. Blank lines, CRLF line endings and extra whitespace
+    /// between the numbers are ignored. Duplicate numbers are allowed, use [`parse_bingo`] to reject them.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let lines = numbered_lines(input)
+            .filter(|(_, l)| !l.trim().is_empty())
+            .collect::<Vec<NumberedLine>>();
+        parse_board(&lines, None, &ParseOptions::default())
+    }
+}
+
+/// Parses a complete puzzle input: a line of comma separated drawn values followed by boards separated
+/// by one or more blank lines. CRLF line endings and extra whitespace are tolerated anywhere.
+///
+/// ## Example
+/// ```
+/// # use day_4::{parse_bingo, ParseOptions};
+/// let input = "7, 4, 9\r\n\r\n\r\n22 13 17 11  0\r\n 8  2 23  4 24\r\n21  9 14 16  7\r\n 6 10  3 18  5\r\n 1 12 20 15 19\r\n";
+/// let (values, boards) = parse_bingo(input, &ParseOptions::default()).unwrap();
+/// assert_eq!(vec![7, 4, 9], values);
+/// assert_eq!(1, boards.len());
+/// ```
+pub fn parse_bingo(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<(Vec<usize>, Vec<Board>), BoardParseError> {
+    let mut lines = numbered_lines(input).skip_while(|(_, l)| l.trim().is_empty());
+
+    // Parse the line containing the values
+    let (line_no, value_line) = lines.next().ok_or(BoardParseError {
+        board: None,
+        line: 1,
+        column: 1,
+        kind: BoardParseErrorKind::MissingValues,
+    })?;
+    let values = value_line
+        .split(',')
+        .scan(0, |offset, token| {
+            let start = column(value_line, *offset + token.len() - token.trim_start().len());
+            *offset += token.len() + 1;
+            Some((start, token.trim()))
+        })
+        .map(|(column, token)| {
+            token.parse::<usize>().map_err(|_| BoardParseError {
+                board: None,
+                line: line_no,
+                column,
+                kind: BoardParseErrorKind::InvalidNumber(token.to_string()),
+            })
+        })
+        .collect::<Result<Vec<usize>, BoardParseError>>()?;
+
+    // Group the remaining lines into boards, where any run of blank lines separates two boards
+    let mut boards = vec![];
+    let mut current: Vec<NumberedLine> = vec![];
+    for (line_no, line) in lines.chain([(0, "")]) {
+        if !line.trim().is_empty() {
+            current.push((line_no, line));
+        } else if !current.is_empty() {
+            boards.push(parse_board(&current, Some(boards.len()), options)?);
+            current.clear();
+        }
+    }
+    Ok((values, boards))
+}
+
+#[cfg(test)]
+fn parse_error(input: &str, reject_duplicates: bool) -> BoardParseError {
+    parse_bingo(input, &ParseOptions { reject_duplicates })
+        .expect_err("expected a parse error")
+}
+
+#[test]
+fn test_parse_bingo_tolerates_whitespace() {
+    let lf = include_str!("input.txt");
+    let crlf = format!(
+        "\r\n{}\r\n\r\n",
+        lf.replace("\n\n", "\n  \n\n\t\n")
+            .replace(' ', "  ")
+            .replace('\n', "\r\n")
+    );
+    let expected = parse_bingo(lf, &ParseOptions::default()).unwrap();
+    let parsed = parse_bingo(&crlf, &ParseOptions::default()).unwrap();
+    assert_eq!(expected.0, parsed.0);
+    assert_eq!(expected.1.len(), parsed.1.len());
+    assert_eq!(100, parsed.1.len());
+}
+
+#[test]
+fn test_parse_bingo_errors() {
+    let board = "1 2 3 4 5\n6 7 8 9 10\n11 12 13 14 15\n16 17 18 19 20\n21 22 23 24 25";
+    assert_eq!(
+        BoardParseError {
+            board: None,
+            line: 1,
+            column: 4,
+            kind: BoardParseErrorKind::InvalidNumber("x".to_string()),
+        },
+        parse_error(&format!("1, x\n\n{}", board), false)
+    );
+    assert_eq!(
+        BoardParseError {
+            board: Some(1),
+            line: 11,
+            column: 7,
+            kind: BoardParseErrorKind::InvalidNumber("-3".to_string()),
+        },
+        parse_error(
+            &format!("1\n\n{}\n\n{}", board, board.replace(" 13", " -3")),
+            false
+        )
+    );
+    assert_eq!(
+        BoardParseErrorKind::TooManyColumns,
+        parse_error(&format!("1\n\n{}", board.replace("10", "10 26")), false).kind
+    );
+    assert_eq!(
+        BoardParseErrorKind::TooFewColumns(4),
+        parse_error(&format!("1\n\n{}", board.replace(" 10", "")), false).kind
+    );
+    assert_eq!(
+        BoardParseErrorKind::TooManyRows,
+        parse_error(&format!("1\n\n{}\n1 2 3 4 5", board), false).kind
+    );
+    assert_eq!(
+        BoardParseError {
+            board: Some(0),
+            line: 5,
+            column: 10,
+            kind: BoardParseErrorKind::TooFewRows(3),
+        },
+        parse_error("1\n\n1 2 3 4 5\n1 2 3 4 5\n1 2 3 4 5", false)
+    );
+    assert_eq!(
+        BoardParseErrorKind::MissingValues,
+        parse_error("\n\n", false).kind
+    );
+}
+
+#[test]
+fn test_parse_bingo_error_columns_count_characters() {
+    // The no-break spaces take two bytes each but are a single column
+    let board = "1 2 3 4 5\n6 7 8 9 10\n11 12 13 14 15\n16 17 18 19 20\n21 22 23 24 25";
+    let err = parse_error(&format!("1,\u{a0}x\n\n{}", board), false);
+    assert_eq!((1, 4), (err.line, err.column));
+    let err = parse_error(
+        &format!("1\n\n{}", board.replace("1 2", "1\u{a0}2 x")),
+        false,
+    );
+    assert_eq!((3, 5), (err.line, err.column));
+    let err = parse_error(
+        &format!("1\n\n{}", board.replace("6 7 8 9 10", "6\u{a0}7")),
+        false,
+    );
+    assert_eq!(BoardParseErrorKind::TooFewColumns(2), err.kind);
+    assert_eq!((4, 4), (err.line, err.column));
+}
+
+#[test]
+fn test_parse_bingo_duplicates() {
+    let board = "1 2 3 4 5\n6 7 8 9 10\n11 12 13 14 15\n16 17 18 19 20\n21 22 23 24 1";
+    let input = format!("1\n\n{}", board);
+    assert!(parse_bingo(&input, &ParseOptions::default()).is_ok());
+    let err = parse_error(&input, true);
+    assert_eq!(BoardParseErrorKind::DuplicateNumber(1), err.kind);
+    assert_eq!((7, 13), (err.line, err.column));
+    assert_eq!(
+        "duplicate number 1 in board 0 at line 7, column 13",
+        err.to_string()
+    );
+}
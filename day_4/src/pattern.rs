@@ -0,0 +1,206 @@
+use crate::Board;
+
+#[cfg(test)]
+use crate::get_test_board;
+
+/// The number of rows (and columns) on a Bingo board
+const SIZE: usize = 5;
+
+/// The bits of a mask that stand for a cell of the board
+const BOARD_MASK: u32 = (1 << (SIZE * SIZE)) - 1;
+
+/// Describes a shape of cells that counts as a bingo once every cell in the shape has been visited.
+/// Each cell on the board is represented by a single bit in a `u32` mask where the bit index is
+/// `row * 5 + col`, so a pattern is really just one or more masks. Patterns such as [`WinPattern::Rows`]
+/// are made up of several masks (one per row), any one of which is enough to win.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WinPattern {
+    /// Any complete horizontal row
+    Rows,
+    /// Any complete vertical column
+    Columns,
+    /// The diagonal from the top-left to the bottom-right corner
+    Diagonal,
+    /// The diagonal from the top-right to the bottom-left corner
+    AntiDiagonal,
+    /// The four corner cells of the board
+    FourCorners,
+    /// Both diagonals at the same time
+    X,
+    /// Every cell on the board (also known as a full card)
+    Blackout,
+    /// An arbitrary set of cells, see [`WinPattern::custom`]. Bits past the 25 cells of the board
+    /// are ignored, and an empty mask never wins.
+    Custom(u32),
+}
+
+impl WinPattern {
+    /// Returns the patterns used by the original puzzle, which are complete rows and columns.
+    pub fn standard() -> Vec<WinPattern> {
+        vec![WinPattern::Rows, WinPattern::Columns]
+    }
+
+    /// Creates a custom pattern from a list of zero-based row/col indexes. Indexes outside of the
+    /// 5x5 board will panic.
+    ///
+    /// ## Example
+    /// ```
+    /// # use day_4::WinPattern;
+    /// // The middle cell and the four cells around it
+    /// let plus = WinPattern::custom(&[(1, 2), (2, 1), (2, 2), (2, 3), (3, 2)]);
+    /// ```
+    pub fn custom(cells: &[(usize, usize)]) -> Self {
+        WinPattern::Custom(
+            cells
+                .iter()
+                .fold(0, |mask, (row, col)| mask | cell_bit(*row, *col)),
+        )
+    }
+
+    /// Returns all the masks that make up this pattern. The pattern is satisfied when all the cells
+    /// in any one of the returned masks have been visited.
+    pub fn masks(&self) -> Vec<u32> {
+        match self {
+            WinPattern::Rows => (0..SIZE).map(row_mask).collect(),
+            WinPattern::Columns => (0..SIZE).map(col_mask).collect(),
+            WinPattern::Diagonal => vec![diagonal_mask()],
+            WinPattern::AntiDiagonal => vec![anti_diagonal_mask()],
+            WinPattern::FourCorners => vec![
+                cell_bit(0, 0)
+                    | cell_bit(0, SIZE - 1)
+                    | cell_bit(SIZE - 1, 0)
+                    | cell_bit(SIZE - 1, SIZE - 1),
+            ],
+            WinPattern::X => vec![diagonal_mask() | anti_diagonal_mask()],
+            WinPattern::Blackout => vec![BOARD_MASK],
+            WinPattern::Custom(mask) => vec![*mask & BOARD_MASK],
+        }
+    }
+}
+
+/// Returns the mask bit for a zero-based row and column index
+pub(crate) fn cell_bit(row: usize, col: usize) -> u32 {
+    assert!(
+        row < SIZE && col < SIZE,
+        "cell ({}, {}) is outside of the board",
+        row,
+        col
+    );
+    1 << (row * SIZE + col)
+}
+
+fn row_mask(row: usize) -> u32 {
+    (0..SIZE).fold(0, |m, col| m | cell_bit(row, col))
+}
+
+fn col_mask(col: usize) -> u32 {
+    (0..SIZE).fold(0, |m, row| m | cell_bit(row, col))
+}
+
+fn diagonal_mask() -> u32 {
+    (0..SIZE).fold(0, |m, i| m | cell_bit(i, i))
+}
+
+fn anti_diagonal_mask() -> u32 {
+    (0..SIZE).fold(0, |m, i| m | cell_bit(i, SIZE - 1 - i))
+}
+
+impl Board {
+    /// Returns a mask of all the visited cells on the board using the same bit layout as [`WinPattern`].
+    pub(crate) fn visited_mask(&self) -> u32 {
+        self.grid
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, c)| c.visited)
+            .fold(0, |m, (idx, _)| m | 1 << idx)
+    }
+
+    /// Returns the mask that satisfied the pattern if the board currently matches the provided pattern.
+    pub fn matches_pattern(&self, pattern: &WinPattern) -> Option<u32> {
+        let visited = self.visited_mask();
+        pattern
+            .masks()
+            .into_iter()
+            .find(|m| *m != 0 && visited & m == *m)
+    }
+
+    /// Evaluates the board against a set of patterns and returns the first pattern (in the order
+    /// provided) that the board satisfies, along with the mask of the cells that fired it.
+    pub fn winning_pattern<'a>(&self, patterns: &'a [WinPattern]) -> Option<(&'a WinPattern, u32)> {
+        patterns
+            .iter()
+            .find_map(|p| self.matches_pattern(p).map(|m| (p, m)))
+    }
+}
+
+#[test]
+fn test_pattern_masks() {
+    assert_eq!(0b11111, WinPattern::Rows.masks()[0]);
+    assert_eq!(
+        0b00001_00001_00001_00001_00001,
+        WinPattern::Columns.masks()[0]
+    );
+    assert_eq!(
+        0b10000_01000_00100_00010_00001,
+        WinPattern::Diagonal.masks()[0]
+    );
+    assert_eq!(
+        0b10001_00000_00000_00000_10001,
+        WinPattern::FourCorners.masks()[0]
+    );
+    assert_eq!(25, WinPattern::Blackout.masks()[0].count_ones());
+    assert_eq!(9, WinPattern::X.masks()[0].count_ones());
+}
+
+#[test]
+fn test_winning_pattern_diagonal() {
+    let mut board = get_test_board();
+    let patterns = [WinPattern::Rows, WinPattern::Columns, WinPattern::Diagonal];
+    for v in [22, 2, 14, 18] {
+        board.visit_value(v);
+    }
+    assert_eq!(None, board.winning_pattern(&patterns));
+    board.visit_value(19);
+    assert!(!board.has_bingo());
+    assert_eq!(
+        Some((&WinPattern::Diagonal, WinPattern::Diagonal.masks()[0])),
+        board.winning_pattern(&patterns)
+    );
+}
+
+#[test]
+fn test_winning_pattern_four_corners() {
+    let mut board = get_test_board();
+    for v in [22, 0, 1, 19] {
+        board.visit_value(v);
+    }
+    let patterns = [WinPattern::Rows, WinPattern::FourCorners];
+    assert_eq!(
+        Some(&WinPattern::FourCorners),
+        board.winning_pattern(&patterns).map(|w| w.0)
+    );
+    assert_eq!(None, board.matches_pattern(&WinPattern::X));
+}
+
+#[test]
+fn test_winning_pattern_custom() {
+    let mut board = get_test_board();
+    let plus = WinPattern::custom(&[(1, 2), (2, 1), (2, 2), (2, 3), (3, 2)]);
+    assert_eq!(None, board.matches_pattern(&WinPattern::Custom(0)));
+    for v in [23, 9, 14, 16] {
+        board.visit_value(v);
+    }
+    assert_eq!(None, board.matches_pattern(&plus));
+    board.visit_value(3);
+    assert!(board.matches_pattern(&plus).is_some());
+
+    // Bits outside of the board don't stop the pattern from winning
+    let WinPattern::Custom(mask) = plus else {
+        unreachable!()
+    };
+    let outside = WinPattern::Custom(mask | 1 << 25 | 1 << 31);
+    assert_eq!(vec![mask], outside.masks());
+    assert!(board.matches_pattern(&outside).is_some());
+    assert_eq!(None, board.matches_pattern(&WinPattern::Custom(1 << 30)));
+}
@@ -0,0 +1,185 @@
+use std::{
+    fmt,
+    io::{stdout, IsTerminal},
+};
+
+use crate::{Board, Cell, WinPattern};
+
+/// ANSI escape codes used when rendering in colour
+const MARKED: &str = "\x1b[1;32m";
+const WINNING: &str = "\x1b[1;30;43m";
+const RESET: &str = "\x1b[0m";
+
+/// The gap between boards rendered side by side
+const GAP: &str = "   ";
+
+/// Renders boards as aligned text grids. Marked cells are highlighted in green and the cells of the
+/// winning line (if any) in yellow when using colour. Without colour, marked cells are wrapped in
+/// brackets like `[22]` and winning cells in asterisks like `*22*`.
+#[derive(Clone, Debug)]
+pub struct Renderer {
+    /// Uses ANSI colour codes rather than brackets
+    pub color: bool,
+    /// The patterns used to find the winning line to highlight
+    pub patterns: Vec<WinPattern>,
+}
+
+impl Renderer {
+    /// Creates a renderer that uses colour when stdout is a terminal and brackets otherwise
+    pub fn detect() -> Self {
+        Self::new(stdout().is_terminal())
+    }
+
+    /// Creates a renderer that highlights the standard row and column patterns
+    pub fn new(color: bool) -> Self {
+        Self {
+            color,
+            patterns: WinPattern::standard(),
+        }
+    }
+
+    /// Renders a single board, one line per row
+    pub fn render(&self, board: &Board) -> String {
+        self.lines(board, width(board)).join("\n")
+    }
+
+    /// Renders the boards side by side, `per_row` boards at a time, with a header containing the board's
+    /// index above each board. All the boards use the same cell width so that they line up.
+    pub fn render_boards(&self, boards: &[Board], per_row: usize) -> String {
+        let width = boards.iter().map(width).max().unwrap_or(1);
+        let board_width = (width + 2) * 5;
+        boards
+            .chunks(per_row.max(1))
+            .enumerate()
+            .map(|(chunk, boards)| {
+                let headers = (0..boards.len())
+                    .map(|i| {
+                        format!(
+                            "{:<board_width$}",
+                            format!("Board {}", chunk * per_row.max(1) + i),
+                            board_width = board_width
+                        )
+                    })
+                    .collect::<Vec<String>>();
+                let grids = boards
+                    .iter()
+                    .map(|b| self.lines(b, width))
+                    .collect::<Vec<Vec<String>>>();
+                let rows = (0..5).map(|row| {
+                    grids
+                        .iter()
+                        .map(|g| g[row].as_str())
+                        .collect::<Vec<&str>>()
+                        .join(GAP)
+                });
+                std::iter::once(headers.join(GAP).trim_end().to_string())
+                    .chain(rows)
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+
+    /// Returns the rendered rows of the board, where each cell takes up `width + 2` visible characters
+    fn lines(&self, board: &Board, width: usize) -> Vec<String> {
+        let winning = board
+            .winning_pattern(&self.patterns)
+            .map_or(0, |(_, mask)| mask);
+        board
+            .grid
+            .iter()
+            .enumerate()
+            .map(|(row, cells)| {
+                cells
+                    .iter()
+                    .enumerate()
+                    .map(|(col, cell)| {
+                        let highlight = winning & 1 << (row * 5 + col) != 0;
+                        self.cell(cell, width, highlight)
+                    })
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    fn cell(&self, cell: &Cell, width: usize, winning: bool) -> String {
+        let value = format!("{:>width$}", cell.value, width = width);
+        match (self.color, cell.visited, winning) {
+            (true, _, true) => format!("{} {} {}", WINNING, value, RESET),
+            (true, true, false) => format!("{} {} {}", MARKED, value, RESET),
+            (false, _, true) => format!("*{}*", value),
+            (false, true, false) => format!("[{}]", value),
+            (_, false, _) => format!(" {} ", value),
+        }
+    }
+}
+
+/// Returns the number of digits in the widest value on the board
+fn width(board: &Board) -> usize {
+    board
+        .grid
+        .iter()
+        .flatten()
+        .map(|c| c.value.to_string().len())
+        .max()
+        .unwrap_or(1)
+}
+
+impl fmt::Display for Board {
+    /// Renders the board without colour, see [`Renderer`]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Renderer::new(false).render(self))
+    }
+}
+
+impl fmt::Debug for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Board")
+            .field("grid", &self.grid)
+            .field("visited_count_by_row", &self.visited_count_by_row)
+            .field("visited_count_by_col", &self.visited_count_by_col)
+            .finish()
+    }
+}
+
+#[test]
+fn test_render_marked() {
+    let (_, mut boards) = crate::get_example_game();
+    boards[0].visit_value(22);
+    boards[0].visit_value(7);
+    assert_eq!(
+        "[22] 13  17  11   0 \n  8   2  23   4  24 \n 21   9  14  16 [ 7]\n  6  10   3  18   5 \n  1  12  20  15  19 ",
+        boards[0].to_string()
+    );
+}
+
+#[test]
+fn test_render_winning_line_color() {
+    let (_, mut boards) = crate::get_example_game();
+    for v in [8, 2, 23, 4, 24, 13] {
+        boards[0].visit_value(v);
+    }
+    let rendered = Renderer::new(true).render(&boards[0]);
+    let lines = rendered.lines().collect::<Vec<&str>>();
+    assert_eq!(
+        format!("{} 13 {}", MARKED, RESET),
+        lines[0][4..4 + MARKED.len() + 4 + RESET.len()]
+    );
+    assert_eq!(5, lines[1].matches(WINNING).count());
+    assert!(!lines[2].contains('\x1b'));
+    let plain = Renderer::new(false).render(&boards[0]);
+    assert_eq!("* 8** 2**23** 4**24*", plain.lines().nth(1).unwrap());
+}
+
+#[test]
+fn test_render_boards_side_by_side() {
+    let (_, boards) = crate::get_example_game();
+    let rendered = Renderer::new(false).render_boards(&boards, 2);
+    let lines = rendered.lines().collect::<Vec<&str>>();
+    assert_eq!(13, lines.len());
+    assert_eq!("Board 0                Board 1", lines[0]);
+    assert_eq!(" 22  13  17  11   0      3  15   0   2  22 ", lines[1]);
+    assert_eq!("", lines[6]);
+    assert_eq!("Board 2", lines[7]);
+}
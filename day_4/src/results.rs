@@ -0,0 +1,159 @@
+use crate::Board;
+
+/// Decides which boards are reported when several boards win on the same draw
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TiePolicy {
+    /// Only the tied board that comes first in the input
+    First,
+    /// Only the tied board that comes last in the input
+    Last,
+    /// Every tied board, in input order
+    All,
+}
+
+/// The result of a single board that won
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoardScore {
+    /// The zero-based index of the board in the input
+    pub board: usize,
+    /// The zero-based index of the draw that the board won on
+    pub round: usize,
+    /// The value that was drawn when the board won
+    pub value: usize,
+    /// The drawn value multiplied by the sum of the board's unvisited cells
+    pub score: usize,
+}
+
+/// The winning and loosing boards of a game after applying a [`TiePolicy`]. Both vectors are empty
+/// when no board wins, otherwise each contains one board (or every tied board for [`TiePolicy::All`]).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BingoResults {
+    /// The boards that won on the earliest draw
+    pub winners: Vec<BoardScore>,
+    /// The boards that won on the latest draw. Boards that never win are not considered.
+    pub losers: Vec<BoardScore>,
+}
+
+/// Evaluates each board against the values and returns the winning and loosing boards. Unlike comparing
+/// rounds one board at a time, all the boards that win on the earliest (or latest) draw are collected
+/// first and then the tie policy picks which of them are reported, so the result never depends on
+/// which tied board happened to be looked at last.
+pub fn evaluate_bingo_boards_with_policy(
+    values: &[usize],
+    boards: Vec<Board>,
+    policy: TiePolicy,
+) -> BingoResults {
+    let scores = boards
+        .into_iter()
+        .enumerate()
+        .filter_map(|(idx, mut board)| {
+            values
+                .iter()
+                .enumerate()
+                .find(|(_, v)| board.visit_value(**v))
+                .map(|(round, value)| BoardScore {
+                    board: idx,
+                    round,
+                    value: *value,
+                    score: value * board.sum_unvisited(),
+                })
+        })
+        .collect::<Vec<BoardScore>>();
+
+    let (first, last) = match (
+        scores.iter().map(|s| s.round).min(),
+        scores.iter().map(|s| s.round).max(),
+    ) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return BingoResults::default(),
+    };
+    let tied = |round: usize| -> Vec<BoardScore> {
+        let mut tied = scores.iter().filter(|s| s.round == round).cloned();
+        match policy {
+            TiePolicy::First => tied.take(1).collect(),
+            TiePolicy::Last => tied.next_back().into_iter().collect(),
+            TiePolicy::All => tied.collect(),
+        }
+    };
+    BingoResults {
+        winners: tied(first),
+        losers: tied(last),
+    }
+}
+
+/// Returns the example boards with the winning and loosing boards duplicated, so that two boards tie for
+/// the win (boards 0 and 2) and two boards tie for the loss (boards 1 and 3).
+#[cfg(test)]
+fn get_tied_game() -> (Vec<usize>, Vec<Board>) {
+    let (values, boards) = crate::get_example_game();
+    let boards = vec![
+        boards[2].clone(),
+        boards[1].clone(),
+        boards[2].clone(),
+        boards[1].clone(),
+        boards[0].clone(),
+    ];
+    (values, boards)
+}
+
+#[test]
+fn test_tie_policy_first() {
+    let (values, boards) = get_tied_game();
+    let results = evaluate_bingo_boards_with_policy(&values, boards, TiePolicy::First);
+    assert_eq!(vec![(0, 4512)], summarize(&results.winners));
+    assert_eq!(vec![(1, 1924)], summarize(&results.losers));
+}
+
+#[test]
+fn test_tie_policy_last() {
+    let (values, boards) = get_tied_game();
+    let results = evaluate_bingo_boards_with_policy(&values, boards, TiePolicy::Last);
+    assert_eq!(vec![(2, 4512)], summarize(&results.winners));
+    assert_eq!(vec![(3, 1924)], summarize(&results.losers));
+}
+
+#[test]
+fn test_tie_policy_all() {
+    let (values, boards) = get_tied_game();
+    let results = evaluate_bingo_boards_with_policy(&values, boards, TiePolicy::All);
+    assert_eq!(vec![(0, 4512), (2, 4512)], summarize(&results.winners));
+    assert_eq!(vec![(1, 1924), (3, 1924)], summarize(&results.losers));
+    assert!(results
+        .winners
+        .iter()
+        .all(|s| s.round == 11 && s.value == 24));
+}
+
+#[test]
+fn test_evaluators_agree_on_ties() {
+    let (values, boards) = get_tied_game();
+    let bitboards = boards
+        .iter()
+        .map(crate::BitBoard::from)
+        .collect::<Vec<crate::BitBoard>>();
+    let expected = (4512, 1924);
+    assert_eq!(
+        expected,
+        crate::evaluate_bingo_boards(values.clone(), boards.clone())
+    );
+    assert_eq!(
+        expected,
+        crate::evaluate_bingo_boards_indexed(values.clone(), boards)
+    );
+    assert_eq!(
+        expected,
+        crate::evaluate_bingo_bitboards(&values, &bitboards)
+    );
+}
+
+#[test]
+fn test_tie_policy_no_winner() {
+    let (values, boards) = get_tied_game();
+    let results = evaluate_bingo_boards_with_policy(&values[..5], boards, TiePolicy::All);
+    assert_eq!(BingoResults::default(), results);
+}
+
+#[cfg(test)]
+fn summarize(scores: &[BoardScore]) -> Vec<(usize, usize)> {
+    scores.iter().map(|s| (s.board, s.score)).collect()
+}
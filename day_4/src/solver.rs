@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+
+use crate::{Board, WinPattern};
+
+/// What the draw order should achieve for the target board
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Goal {
+    /// The target board wins before every other board, without sharing the draw with another board
+    WinFirst,
+    /// Every other board wins before the target board, without sharing the draw with the target board
+    LoseLast,
+}
+
+/// The result of searching for a draw order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SearchOutcome {
+    /// A draw order that achieves the goal in the fewest possible draws
+    Optimal(Vec<usize>),
+    /// A draw order that achieves the goal, but the search ran out of nodes before it could prove that
+    /// there is no shorter one
+    Feasible(Vec<usize>),
+    /// The search was exhaustive and no draw order can achieve the goal
+    Impossible,
+    /// The search ran out of nodes before finding a draw order or proving that there isn't one
+    Unknown,
+}
+
+/// Controls what counts as a win and how much work the search may do
+#[derive(Clone, Debug)]
+pub struct SolverConfig {
+    /// The patterns that count as a win, see [`WinPattern`]
+    pub patterns: Vec<WinPattern>,
+    /// The maximum number of search nodes to visit before giving up on proving optimality
+    pub max_nodes: usize,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            patterns: WinPattern::standard(),
+            max_nodes: 1_000_000,
+        }
+    }
+}
+
+/// A set of values, stored as a bitset over the dense ids assigned by [`Lines`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ValueSet(Vec<u64>);
+
+impl ValueSet {
+    fn empty(size: usize) -> Self {
+        ValueSet(vec![0; size.div_ceil(64)])
+    }
+
+    fn insert(&mut self, id: usize) {
+        self.0[id / 64] |= 1 << (id % 64);
+    }
+
+    fn union(&self, other: &ValueSet) -> ValueSet {
+        ValueSet(self.0.iter().zip(&other.0).map(|(a, b)| a | b).collect())
+    }
+
+    fn is_subset(&self, other: &ValueSet) -> bool {
+        self.0.iter().zip(&other.0).all(|(a, b)| a & !b == 0)
+    }
+
+    fn len(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns the number of values in this set that are not in the other set
+    fn difference_len(&self, other: &ValueSet) -> usize {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .map(|(a, b)| (a & !b).count_ones() as usize)
+            .sum()
+    }
+
+    fn ids(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(w, word)| {
+            (0..64)
+                .filter(move |bit| word & 1 << bit != 0)
+                .map(move |bit| w * 64 + bit)
+        })
+    }
+}
+
+/// The winning lines of every board, where each line is the set of values that completes it. A board
+/// wins as soon as every value of any one of its lines has been drawn, no matter the order.
+struct Lines {
+    // Maps a dense id back to its value
+    values: Vec<usize>,
+    // The lines of each board
+    boards: Vec<Vec<ValueSet>>,
+}
+
+impl Lines {
+    fn new(boards: &[Board], patterns: &[WinPattern]) -> Self {
+        let mut ids: HashMap<usize, usize> = HashMap::new();
+        let mut values = vec![];
+        for cell in boards.iter().flat_map(|b| b.grid.iter().flatten()) {
+            ids.entry(cell.value).or_insert_with(|| {
+                values.push(cell.value);
+                values.len() - 1
+            });
+        }
+        let masks = patterns
+            .iter()
+            .flat_map(WinPattern::masks)
+            .filter(|m| *m != 0)
+            .collect::<Vec<u32>>();
+        let boards = boards
+            .iter()
+            .map(|b| {
+                let cells = b.grid.iter().flatten().collect::<Vec<_>>();
+                masks
+                    .iter()
+                    .map(|m| {
+                        let mut line = ValueSet::empty(values.len());
+                        (0..25)
+                            .filter(|idx| m & 1 << idx != 0)
+                            .for_each(|idx| line.insert(ids[&cells[idx].value]));
+                        line
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { values, boards }
+    }
+
+    /// Returns true if the board has a line whose values are all in the set
+    fn completes(&self, board: usize, set: &ValueSet) -> bool {
+        self.boards[board].iter().any(|l| l.is_subset(set))
+    }
+
+    /// Returns the values of the set in ascending order
+    fn draw_order(&self, set: &ValueSet) -> Vec<usize> {
+        let mut order = set.ids().map(|id| self.values[id]).collect::<Vec<usize>>();
+        order.sort_unstable();
+        order
+    }
+}
+
+/// Searches for a draw order that makes the target board win first (or lose last) in as few draws as
+/// possible, where a win is any of the configured patterns. If the search shows that no such order
+/// exists it returns [`SearchOutcome::Impossible`].
+///
+/// Since a board wins once all the values of one of its lines have been drawn, regardless of order,
+/// the search is over sets of values rather than sequences:
+///
+/// * To win first, the target needs a line whose values don't also complete a line of another board.
+///   The smallest such line is optimal and if every line is blocked by another board it's impossible.
+///   This is always decided exactly.
+/// * To lose last, every other board needs one of its lines drawn (the union of which can't complete
+///   a line of the target), followed by the rest of one of the target's lines. This is a set-cover
+///   style problem, so it's solved with a depth-first branch and bound search that is limited by
+///   [`SolverConfig::max_nodes`].
+///
+/// ## Example
+/// ```
+/// # use day_4::{find_draw_order, Goal, SearchOutcome, SolverConfig};
+/// let (_, boards) = day_4::parse_input();
+/// let outcome = find_draw_order(&boards, 0, Goal::WinFirst, &SolverConfig::default());
+/// assert!(matches!(outcome, SearchOutcome::Optimal(order) if order.len() == 5));
+/// ```
+pub fn find_draw_order(
+    boards: &[Board],
+    target: usize,
+    goal: Goal,
+    config: &SolverConfig,
+) -> SearchOutcome {
+    let lines = Lines::new(boards, &config.patterns);
+    match goal {
+        Goal::WinFirst => win_first(&lines, target),
+        Goal::LoseLast => lose_last(&lines, target, config.max_nodes),
+    }
+}
+
+fn win_first(lines: &Lines, target: usize) -> SearchOutcome {
+    lines.boards[target]
+        .iter()
+        .filter(|line| {
+            (0..lines.boards.len())
+                .filter(|b| *b != target)
+                .all(|b| !lines.completes(b, line))
+        })
+        .min_by_key(|line| line.len())
+        .map(|line| SearchOutcome::Optimal(lines.draw_order(line)))
+        .unwrap_or(SearchOutcome::Impossible)
+}
+
+/// The state of the lose last branch and bound search
+struct LoseLastSearch<'a> {
+    lines: &'a Lines,
+    target: usize,
+    // The other boards in input order
+    others: Vec<usize>,
+    nodes: usize,
+    max_nodes: usize,
+    // The best (drawn set, target line) found so far and its number of draws
+    best: Option<(ValueSet, ValueSet)>,
+    best_len: usize,
+}
+
+impl<'a> LoseLastSearch<'a> {
+    /// Returns the target line that needs the fewest additional values to be drawn after `drawn`
+    fn target_remaining(&self, drawn: &ValueSet) -> Option<&'a ValueSet> {
+        self.lines.boards[self.target]
+            .iter()
+            .min_by_key(|l| l.difference_len(drawn))
+    }
+
+    fn search(&mut self, depth: usize, drawn: ValueSet) {
+        self.nodes += 1;
+        if self.nodes > self.max_nodes {
+            return;
+        }
+        // The target can't win before (or alongside) the other boards
+        if self.lines.completes(self.target, &drawn) {
+            return;
+        }
+
+        // Skip the boards that the values drawn so far already complete
+        let mut depth = depth;
+        while depth < self.others.len() && self.lines.completes(self.others[depth], &drawn) {
+            depth += 1;
+        }
+
+        if depth == self.others.len() {
+            if let Some(line) = self.target_remaining(&drawn) {
+                let total = drawn.len() + line.difference_len(&drawn);
+                if total < self.best_len {
+                    self.best_len = total;
+                    self.best = Some((drawn.clone(), line.clone()));
+                }
+            }
+            return;
+        }
+
+        // Every remaining board (and the target) needs at least its cheapest line drawn
+        let target_bound = self
+            .target_remaining(&drawn)
+            .map_or(0, |l| l.difference_len(&drawn));
+        let board_bound = self.others[depth..]
+            .iter()
+            .map(|b| {
+                self.lines.boards[*b]
+                    .iter()
+                    .map(|l| l.difference_len(&drawn))
+                    .min()
+                    .unwrap_or(0)
+            })
+            .max()
+            .unwrap_or(0);
+        if drawn.len() + target_bound.max(board_bound) >= self.best_len {
+            return;
+        }
+
+        // Try the cheapest lines of the next board first so that a good solution is found early
+        let board = self.others[depth];
+        let mut candidates = self.lines.boards[board].iter().collect::<Vec<&ValueSet>>();
+        candidates.sort_by_key(|l| l.difference_len(&drawn));
+        for line in candidates {
+            self.search(depth + 1, drawn.union(line));
+        }
+    }
+}
+
+fn lose_last(lines: &Lines, target: usize, max_nodes: usize) -> SearchOutcome {
+    let others = (0..lines.boards.len())
+        .filter(|b| *b != target)
+        .collect::<Vec<usize>>();
+
+    let mut search = LoseLastSearch {
+        lines,
+        target,
+        others,
+        nodes: 0,
+        max_nodes,
+        best: None,
+        best_len: usize::MAX,
+    };
+    search.search(0, ValueSet::empty(lines.values.len()));
+    let exhausted = search.nodes <= max_nodes;
+
+    match search.best {
+        Some((drawn, line)) => {
+            // Draw everything the other boards need and then finish the target
+            let mut order = lines.draw_order(&drawn);
+            let rest = lines
+                .draw_order(&line)
+                .into_iter()
+                .filter(|v| !order.contains(v))
+                .collect::<Vec<usize>>();
+            order.extend(rest);
+            if exhausted {
+                SearchOutcome::Optimal(order)
+            } else {
+                SearchOutcome::Feasible(order)
+            }
+        }
+        None if exhausted => SearchOutcome::Impossible,
+        None => SearchOutcome::Unknown,
+    }
+}
+
+/// Plays the order and checks that the target is the only board finishing first (or last)
+#[cfg(test)]
+fn assert_goal(boards: &[Board], target: usize, goal: Goal, order: &[usize]) {
+    let summary = crate::BingoGame::new(boards.to_vec()).play(order);
+    let finishes = &summary.finishing_order;
+    assert!(summary.never_won.is_empty() || goal == Goal::WinFirst);
+    match goal {
+        Goal::WinFirst => {
+            assert_eq!(target, finishes[0].board);
+            assert_eq!(order.len() - 1, finishes[0].round);
+            assert!(finishes.len() == 1 || finishes[1].round > finishes[0].round);
+        }
+        Goal::LoseLast => {
+            let last = finishes.last().unwrap();
+            assert_eq!(target, last.board);
+            assert_eq!(order.len() - 1, last.round);
+            assert!(finishes[finishes.len() - 2].round < last.round);
+        }
+    }
+}
+
+#[test]
+fn test_find_draw_order_win_first() {
+    let (_, boards) = crate::get_example_game();
+    for target in 0..boards.len() {
+        match find_draw_order(&boards, target, Goal::WinFirst, &SolverConfig::default()) {
+            SearchOutcome::Optimal(order) => {
+                assert_eq!(5, order.len());
+                assert_goal(&boards, target, Goal::WinFirst, &order);
+            }
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        }
+    }
+}
+
+#[test]
+fn test_find_draw_order_lose_last() {
+    let (_, boards) = crate::get_example_game();
+    for target in 0..boards.len() {
+        match find_draw_order(&boards, target, Goal::LoseLast, &SolverConfig::default()) {
+            SearchOutcome::Optimal(order) => assert_goal(&boards, target, Goal::LoseLast, &order),
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        }
+    }
+}
+
+#[test]
+fn test_find_draw_order_impossible() {
+    // A board that is a copy of another board always finishes alongside it
+    let (_, boards) = crate::get_example_game();
+    let boards = vec![boards[0].clone(), boards[1].clone(), boards[0].clone()];
+    let config = SolverConfig::default();
+    assert_eq!(
+        SearchOutcome::Impossible,
+        find_draw_order(&boards, 0, Goal::WinFirst, &config)
+    );
+    assert_eq!(
+        SearchOutcome::Impossible,
+        find_draw_order(&boards, 2, Goal::LoseLast, &config)
+    );
+    assert!(matches!(
+        find_draw_order(&boards, 1, Goal::WinFirst, &config),
+        SearchOutcome::Optimal(_)
+    ));
+}
+
+#[test]
+fn test_find_draw_order_budget() {
+    let (_, boards) = crate::parse_input();
+    let config = SolverConfig {
+        max_nodes: 2_000,
+        ..SolverConfig::default()
+    };
+    match find_draw_order(&boards, 0, Goal::LoseLast, &config) {
+        SearchOutcome::Optimal(order) | SearchOutcome::Feasible(order) => {
+            assert_goal(&boards, 0, Goal::LoseLast, &order)
+        }
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+}
@@ -1,5 +1,80 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use day_5::{count_overlapping_points_part_1, count_overlapping_points_part_2, parse_input};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use day_5::{
+    count_overlapping_points_analytic, count_overlapping_points_part_1,
+    count_overlapping_points_part_2, count_overlapping_points_tiled, parse_input, Counter, Grid,
+    Layer, Line, Point, Raster, TwoBit,
+};
+
+#[path = "../src/xorshift.rs"]
+mod xorshift;
+use xorshift::XorShift;
+
+/// Returns `count` random horizontal, vertical and diagonal lines spanning up to `size` points, which
+/// makes for far longer lines than the puzzle input has.
+fn long_lines(count: usize, size: isize, seed: u64) -> Vec<Line> {
+    let mut random = XorShift::new(seed);
+    let mut next = move |n: isize| random.below(n);
+    (0..count)
+        .map(|i| {
+            let (x, y) = (next(size), next(size));
+            let b = match i % 3 {
+                0 => Point::from(next(size), y),
+                1 => Point::from(x, next(size)),
+                _ => {
+                    let d = next(size) - x.max(y);
+                    Point::from(x + d, y + d)
+                }
+            };
+            Line::new(Point::from(x, y), b)
+        })
+        .collect()
+}
+
+/// Returns the ends of each line of the puzzle input
+fn input_ends() -> Vec<(Point, Point)> {
+    parse_input()
+        .iter()
+        .map(|l| {
+            let mut points = l.points();
+            let a = points.next().unwrap();
+            (a, points.last().unwrap_or(a))
+        })
+        .collect()
+}
+
+/// Returns a range of numbers between the two provided values, decreasing if the first value is
+/// higher than the second
+fn range(a: isize, b: isize) -> Vec<isize> {
+    if a > b {
+        (b..=a).rev().collect::<Vec<isize>>()
+    } else {
+        (a..=b).collect::<Vec<isize>>()
+    }
+}
+
+/// Returns the points of a horizontal, vertical or diagonal line the way `Line::points` used to,
+/// collecting two `range` vectors and then every point into a vector. The vector of Y values is
+/// copied for every X value, like `iproduct!` did.
+fn collected_points(a: Point, b: Point) -> Vec<Point> {
+    let (xs, ys) = (range(a.x(), b.x()), range(a.y(), b.y()));
+    if a.x() != b.x() && a.y() != b.y() {
+        xs.into_iter()
+            .zip(ys)
+            .map(|(x, y)| Point::from(x, y))
+            .collect()
+    } else {
+        xs.into_iter()
+            .flat_map(|x| ys.clone().into_iter().map(move |y| Point::from(x, y)))
+            .collect()
+    }
+}
+
+/// Returns a grid with the lines pushed into it, counting each point with `C`
+fn grid_with<C: Counter>(lines: Vec<Line>) -> Grid<C> {
+    let mut grid = Grid::<C>::with_counter(&lines, true, Raster::Exact);
+    grid.push_lines(lines);
+    grid
+}
 
 fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("count_overlapping_points_part_1", |b| {
@@ -14,6 +89,102 @@ fn criterion_benchmark(c: &mut Criterion) {
             count_overlapping_points_part_2(input.clone());
         })
     });
+    // Walking the points lazily, against the way `Line::points` worked them out before
+    // `LinePoints`, see `collected_points`. Collecting took 2.60 ms against 162 us for the lazy
+    // `LinePoints`.
+    let mut group = c.benchmark_group("line_points");
+    group.bench_function("collected", |b| {
+        let ends = input_ends();
+        b.iter(|| {
+            ends.iter().for_each(|(a, b)| {
+                collected_points(*a, *b).into_iter().for_each(|p| {
+                    black_box(p);
+                })
+            })
+        })
+    });
+    group.bench_function("lazy", |b| {
+        let input = parse_input();
+        b.iter(|| {
+            input.iter().for_each(|l| {
+                l.points().for_each(|p| {
+                    black_box(p);
+                })
+            })
+        })
+    });
+    group.finish();
+
+    c.bench_function("count_overlapping_points_tiled", |b| {
+        let input = parse_input();
+        b.iter(|| {
+            count_overlapping_points_tiled(&input, 256);
+        })
+    });
+    c.bench_function("count_overlapping_points_analytic", |b| {
+        let input = parse_input();
+        b.iter(|| {
+            count_overlapping_points_analytic(&input);
+        })
+    });
+
+    // Part #2 with each of the counters. Both layers of counts took 15.3 MB with a usize, 1.9 MB
+    // with a u8 and 479 kB with a TwoBit, and part #2 took 4.37 ms, 3.19 ms and 4.55 ms. The u8
+    // saves on memory traffic, but unpacking the TwoBit costs more than its smaller size saves.
+    let mut group = c.benchmark_group("counters");
+    group.bench_function("usize", |b| {
+        let input = parse_input();
+        b.iter(|| grid_with::<usize>(input.clone()).overlapping(Layer::All))
+    });
+    group.bench_function("u8", |b| {
+        let input = parse_input();
+        b.iter(|| grid_with::<u8>(input.clone()).overlapping(Layer::All))
+    });
+    group.bench_function("two_bit", |b| {
+        let input = parse_input();
+        b.iter(|| grid_with::<TwoBit>(input.clone()).overlapping(Layer::All))
+    });
+    group.finish();
+
+    // 300 lines across a 20000x20000 grid, where walking every point of every line gets expensive
+    let mut group = c.benchmark_group("long_lines");
+    group.sample_size(10);
+    group.bench_function("count_overlapping_points_part_2", |b| {
+        let input = long_lines(300, 20_000, 1);
+        b.iter(|| {
+            count_overlapping_points_part_2(input.clone());
+        })
+    });
+    group.bench_function("count_overlapping_points_analytic", |b| {
+        let input = long_lines(300, 20_000, 1);
+        b.iter(|| {
+            count_overlapping_points_analytic(&input);
+        })
+    });
+    group.finish();
+
+    // 20000 lines across a 4000x4000 grid, split into tiles that are counted in parallel. The
+    // diagonals stretch the lines' bounding box to about 8000x8000, which is too large for part #2 to
+    // back its grid with vectors, so with a single core it took 9.85 s counting in hashmaps against
+    // 371 ms, 181 ms and 340 ms for tiles of 64, 256 and 1024. On the puzzle input the tiles took
+    // 1.31 ms against 5.28 ms for part #2.
+    let mut group = c.benchmark_group("many_lines");
+    group.sample_size(10);
+    group.bench_function("count_overlapping_points_part_2", |b| {
+        let input = long_lines(20_000, 4_000, 2);
+        b.iter(|| {
+            count_overlapping_points_part_2(input.clone());
+        })
+    });
+    for tile_size in [64, 256, 1024] {
+        group.bench_function(format!("count_overlapping_points_tiled/{tile_size}"), |b| {
+            let input = long_lines(20_000, 4_000, 2);
+            b.iter(|| {
+                count_overlapping_points_tiled(&input, tile_size);
+            })
+        });
+    }
+    group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,90 @@
+use std::fmt::Debug;
+
+use crate::store::{CellValue, Packing};
+
+/// Keeps the number of lines covering a point of a [`crate::Grid`]. A `usize` counts as high as
+/// any input can go, but costs 8 bytes per point, twice over when diagonals are counted as well.
+/// Smaller counters stop counting once they're full, which is fine when all that matters is
+/// whether a point is covered by at least a handful of lines:
+///
+/// - `u8` counts up to 255 lines in a single byte
+/// - [`TwoBit`] counts up to 3 lines, four points to a byte, which is all the puzzle needs to tell
+///   the points covered by 2 or more lines apart
+///
+/// Other counters can be used by implementing this trait along with [`CellValue`].
+///
+/// ## Example
+/// ```
+/// # use day_5::{parse_input, Grid, Layer, Raster, TwoBit};
+/// let lines = parse_input();
+/// let mut grid = Grid::<TwoBit>::with_counter(&lines, true, Raster::Exact);
+/// grid.push_lines(lines);
+/// assert_eq!(15463, grid.overlapping(Layer::All));
+/// ```
+pub trait Counter: CellValue + Debug {
+    /// Returns the counter with one more line. A full counter stays as it is.
+    fn incremented(self) -> Self;
+
+    /// Returns the number of lines counted, which is the most the counter can hold once it's full
+    fn count(self) -> usize;
+}
+
+impl Counter for usize {
+    fn incremented(self) -> Self {
+        self + 1
+    }
+
+    fn count(self) -> usize {
+        self
+    }
+}
+
+impl Counter for u8 {
+    fn incremented(self) -> Self {
+        self.saturating_add(1)
+    }
+
+    fn count(self) -> usize {
+        self as usize
+    }
+}
+
+/// A counter that tells apart 0, 1, 2 and 3 or more lines, packed into 2 bits
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TwoBit(u8);
+
+impl CellValue for TwoBit {
+    const PACKING: Option<Packing<Self>> = Some(Packing {
+        bits: 2,
+        to_bits: |c| c.0,
+        from_bits: TwoBit,
+    });
+}
+
+impl Counter for TwoBit {
+    fn incremented(self) -> Self {
+        TwoBit((self.0 + 1).min(3))
+    }
+
+    fn count(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[test]
+fn test_counters_saturate() {
+    let count = |mut c: u8, n| {
+        (0..n).for_each(|_| c = c.incremented());
+        c.count()
+    };
+    assert_eq!(2, count(0, 2));
+    assert_eq!(255, count(250, 10));
+    let mut c = TwoBit::default();
+    let counts = (0..5)
+        .map(|_| {
+            c = c.incremented();
+            c.count()
+        })
+        .collect::<Vec<usize>>();
+    assert_eq!(vec![1, 2, 3, 3, 3], counts);
+}
@@ -0,0 +1,370 @@
+use std::{error::Error, fmt};
+
+use crate::{Bounds, Counter, Grid, Layer, Point};
+
+/// Images with more pixels than this are refused, which stops a grid spread across billions of
+/// coordinates from trying to allocate an image to match
+const MAX_PIXELS: u128 = 1 << 26;
+
+/// The characters used by the terminal heatmap, from empty to the most overlapping lines
+const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+/// The colours [`ColorMap::Viridis`] blends between
+const VIRIDIS: [[u8; 3]; 5] = [
+    [68, 1, 84],
+    [59, 82, 139],
+    [33, 145, 140],
+    [94, 201, 98],
+    [253, 231, 37],
+];
+
+/// Turns a count into a colour. Points that no lines cover are always black.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMap {
+    /// From black to white
+    #[default]
+    Grayscale,
+    /// From black through red and yellow to white, like heated metal
+    Heat,
+    /// From purple through teal to yellow, which stays readable for colour blind viewers
+    Viridis,
+}
+
+impl ColorMap {
+    /// Returns the colour for `t`, where 0 is no lines and 1 is the most overlapping lines
+    fn color(&self, t: f64) -> [u8; 3] {
+        if t <= 0.0 {
+            return [0, 0, 0];
+        }
+        let t = t.min(1.0);
+        match self {
+            ColorMap::Grayscale => [channel(t); 3],
+            ColorMap::Heat => [
+                channel(t * 3.0),
+                channel(t * 3.0 - 1.0),
+                channel(t * 3.0 - 2.0),
+            ],
+            ColorMap::Viridis => {
+                let scaled = t * (VIRIDIS.len() - 1) as f64;
+                let i = (scaled as usize).min(VIRIDIS.len() - 2);
+                let f = scaled - i as f64;
+                let (a, b) = (VIRIDIS[i], VIRIDIS[i + 1]);
+                [0, 1, 2].map(|c| (a[c] as f64 + (b[c] as f64 - a[c] as f64) * f).round() as u8)
+            }
+        }
+    }
+}
+
+/// Scales a value between 0 and 1 to a colour channel, clamping anything outside of that range
+fn channel(t: f64) -> u8 {
+    (t.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Describes why a heatmap could not be made
+#[derive(Debug)]
+pub enum HeatmapError {
+    /// No lines cover any point of the layer, so there's nothing to draw
+    Empty,
+    /// The image would be too large to hold in memory, try cropping it
+    TooLarge { width: u128, height: u128 },
+    /// The PNG encoder failed
+    Png(png::EncodingError),
+}
+
+impl fmt::Display for HeatmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeatmapError::Empty => write!(f, "no points are covered by any lines"),
+            HeatmapError::TooLarge { width, height } => {
+                write!(f, "a {}x{} image is too large", width, height)
+            }
+            HeatmapError::Png(e) => write!(f, "failed to encode png: {}", e),
+        }
+    }
+}
+
+impl Error for HeatmapError {}
+
+impl From<png::EncodingError> for HeatmapError {
+    fn from(e: png::EncodingError) -> Self {
+        HeatmapError::Png(e)
+    }
+}
+
+/// Draws the counts of a [`Grid`] as an image, with X going right and Y going down like the puzzle's
+/// diagrams. Every pixel is a point and gets brighter the more lines overlap it.
+///
+/// ## Example
+/// ```
+/// # use day_5::{parse_input, ColorMap, Grid, Heatmap, Raster};
+/// let lines = parse_input();
+/// let mut grid = Grid::for_lines(&lines, true, Raster::Exact);
+/// grid.push_lines(lines);
+/// let heatmap = Heatmap { color_map: ColorMap::Heat, log_scale: true, ..Heatmap::default() };
+/// let png = heatmap.to_png(&grid).unwrap();
+/// assert!(png.starts_with(b"\x89PNG"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Heatmap {
+    /// Which of the grid's counts to draw
+    pub layer: Layer,
+    /// The colours used by [`Heatmap::to_ppm`], [`Heatmap::to_png`] and the terminal
+    pub color_map: ColorMap,
+    /// Scales the counts logarithmically, which keeps a few very crowded points from washing out
+    /// everything else
+    pub log_scale: bool,
+    /// Crops the image to the points covered by lines. Otherwise the image also includes 0,0, which
+    /// matches the puzzle's diagrams.
+    pub crop: bool,
+}
+
+impl Heatmap {
+    /// Returns the image as a binary PGM, which is always grayscale
+    pub fn to_pgm<C: Counter>(&self, grid: &Grid<C>) -> Result<Vec<u8>, HeatmapError> {
+        let (width, height, pixels) = self.intensities(grid)?;
+        let mut output = format!("P5\n{} {}\n255\n", width, height).into_bytes();
+        output.extend(pixels.iter().map(|t| ColorMap::Grayscale.color(*t)[0]));
+        Ok(output)
+    }
+
+    /// Returns the image as a binary PPM using the colour map
+    pub fn to_ppm<C: Counter>(&self, grid: &Grid<C>) -> Result<Vec<u8>, HeatmapError> {
+        let (width, height, pixels) = self.intensities(grid)?;
+        let mut output = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        output.extend(pixels.iter().flat_map(|t| self.color_map.color(*t)));
+        Ok(output)
+    }
+
+    /// Returns the image as an RGB PNG using the colour map
+    pub fn to_png<C: Counter>(&self, grid: &Grid<C>) -> Result<Vec<u8>, HeatmapError> {
+        let (width, height, pixels) = self.intensities(grid)?;
+        let data = pixels
+            .iter()
+            .flat_map(|t| self.color_map.color(*t))
+            .collect::<Vec<u8>>();
+        let mut output = vec![];
+        let mut encoder = png::Encoder::new(&mut output, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        writer.finish()?;
+        Ok(output)
+    }
+
+    /// Renders the heatmap as text, at most `columns` characters wide and `rows` lines tall, using
+    /// block characters that get denser as more lines overlap. When the grid is larger than that,
+    /// each character shows the most overlapped point of the area it covers. Works for grids of any
+    /// size since only the covered points are looked at. The colour map isn't used.
+    pub fn to_terminal<C: Counter>(
+        &self,
+        grid: &Grid<C>,
+        columns: usize,
+        rows: usize,
+    ) -> Result<String, HeatmapError> {
+        let bounds = self.bounds(grid)?;
+        let (width, height) = bounds.size();
+        let block_width = width.div_ceil(columns.max(1) as u128);
+        let block_height = height.div_ceil(rows.max(1) as u128);
+        let (columns, rows) = (
+            width.div_ceil(block_width) as usize,
+            height.div_ceil(block_height) as usize,
+        );
+
+        let mut blocks = vec![0; columns * rows];
+        for (p, count) in self.points(grid, &bounds) {
+            let (x, y) = offset(&bounds, &p);
+            let block =
+                &mut blocks[(y / block_height) as usize * columns + (x / block_width) as usize];
+            *block = count.max(*block);
+        }
+        let max = blocks.iter().copied().max().unwrap_or(0);
+        Ok(blocks
+            .chunks(columns)
+            .map(|row| {
+                row.iter()
+                    .map(|c| {
+                        let t = self.scale(*c, max);
+                        SHADES[(t * (SHADES.len() - 1) as f64).ceil() as usize]
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n"))
+    }
+
+    /// Returns the size of the image and the scaled count of each pixel, row by row
+    fn intensities<C: Counter>(
+        &self,
+        grid: &Grid<C>,
+    ) -> Result<(usize, usize, Vec<f64>), HeatmapError> {
+        let bounds = self.bounds(grid)?;
+        let (width, height) = bounds.size();
+        // A grid spanning the whole plane has more pixels than fit a u128
+        if width
+            .checked_mul(height)
+            .is_none_or(|pixels| pixels > MAX_PIXELS)
+        {
+            return Err(HeatmapError::TooLarge { width, height });
+        }
+        let (width, height) = (width as usize, height as usize);
+        let mut counts = vec![0; width * height];
+        for (p, count) in self.points(grid, &bounds) {
+            let (x, y) = offset(&bounds, &p);
+            counts[y as usize * width + x as usize] = count;
+        }
+        let max = counts.iter().copied().max().unwrap_or(0);
+        let pixels = counts.iter().map(|c| self.scale(*c, max)).collect();
+        Ok((width, height, pixels))
+    }
+
+    /// Returns the area of the grid to draw
+    fn bounds<C: Counter>(&self, grid: &Grid<C>) -> Result<Bounds, HeatmapError> {
+        let covered = Bounds::of_points(grid.counts(self.layer).counts().map(|(p, _)| p))
+            .ok_or(HeatmapError::Empty)?;
+        if self.crop {
+            return Ok(covered);
+        }
+        Ok(Bounds {
+            min: Point::from(covered.min.x.min(0), covered.min.y.min(0)),
+            max: Point::from(covered.max.x.max(0), covered.max.y.max(0)),
+        })
+    }
+
+    /// Returns the covered points of the layer that lie inside the bounds
+    fn points<'a, C: Counter>(
+        &self,
+        grid: &'a Grid<C>,
+        bounds: &'a Bounds,
+    ) -> impl Iterator<Item = (Point, usize)> + 'a {
+        grid.counts(self.layer)
+            .counts()
+            .filter(|(p, _)| bounds.contains(p))
+    }
+
+    /// Scales a count to between 0 and 1 relative to the largest count
+    fn scale(&self, count: usize, max: usize) -> f64 {
+        if count == 0 || max == 0 {
+            0.0
+        } else if self.log_scale {
+            (count as f64).ln_1p() / (max as f64).ln_1p()
+        } else {
+            count as f64 / max as f64
+        }
+    }
+}
+
+/// Returns how far the point is from the top left corner of the bounds
+fn offset(bounds: &Bounds, p: &Point) -> (u128, u128) {
+    (
+        (p.x as i128 - bounds.min.x as i128) as u128,
+        (p.y as i128 - bounds.min.y as i128) as u128,
+    )
+}
+
+#[cfg(test)]
+fn get_example_grid() -> Grid {
+    let lines = crate::get_example_lines();
+    let mut grid = Grid::for_lines(&lines, true, crate::Raster::Exact);
+    grid.push_lines(lines);
+    grid
+}
+
+#[test]
+fn test_to_pgm() {
+    let grid = get_example_grid();
+    let pgm = Heatmap::default().to_pgm(&grid).unwrap();
+    let header = b"P5\n10 10\n255\n";
+    assert_eq!(header, &pgm[..header.len()]);
+    let pixels = &pgm[header.len()..];
+    assert_eq!(100, pixels.len());
+    // Three lines overlap at 4,4 and 6,4, two at 2,2, one at 0,0 and none at 1,0
+    assert_eq!(255, pixels[4 * 10 + 4]);
+    assert_eq!(255, pixels[4 * 10 + 6]);
+    assert_eq!(170, pixels[2 * 10 + 2]);
+    assert_eq!(85, pixels[0]);
+    assert_eq!(0, pixels[1]);
+}
+
+#[test]
+fn test_crop_and_log_scale() {
+    let lines = crate::shift(crate::get_example_lines(), 100, -50);
+    let mut grid = Grid::for_lines(&lines, true, crate::Raster::Exact);
+    grid.push_lines(lines);
+    let heatmap = Heatmap {
+        layer: Layer::NonDiagonal,
+        crop: true,
+        log_scale: true,
+        ..Heatmap::default()
+    };
+    let pgm = heatmap.to_pgm(&grid).unwrap();
+    assert!(pgm.starts_with(b"P5\n10 10\n255\n"));
+    let uncropped = Heatmap::default().to_pgm(&grid).unwrap();
+    assert!(uncropped.starts_with(b"P5\n110 51\n255\n"));
+    // ln(2) / ln(3) of the way to white
+    assert!(pgm.contains(&161));
+}
+
+#[test]
+fn test_to_ppm_and_png_match() {
+    let grid = get_example_grid();
+    let heatmap = Heatmap {
+        color_map: ColorMap::Viridis,
+        ..Heatmap::default()
+    };
+    let ppm = heatmap.to_ppm(&grid).unwrap();
+    let pixels = &ppm[b"P6\n10 10\n255\n".len()..];
+    assert_eq!(
+        &[253, 231, 37],
+        &pixels[(4 * 10 + 4) * 3..(4 * 10 + 4) * 3 + 3]
+    );
+
+    let png = heatmap.to_png(&grid).unwrap();
+    let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+    let mut decoded = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut decoded).unwrap();
+    assert_eq!((10, 10), (info.width, info.height));
+    assert_eq!(pixels, &decoded[..info.buffer_size()]);
+}
+
+#[test]
+fn test_too_large() {
+    // Two points in opposite corners of the plane, so the image would have 2^128 pixels
+    let (min, max) = (
+        Point::from(isize::MIN, isize::MIN),
+        Point::from(isize::MAX, isize::MAX),
+    );
+    let lines = vec![crate::Line::new(min, min), crate::Line::new(max, max)];
+    let mut grid = Grid::for_lines(&lines, true, crate::Raster::Exact);
+    grid.push_lines(lines);
+    assert!(matches!(
+        Heatmap::default().to_pgm(&grid),
+        Err(HeatmapError::TooLarge { .. })
+    ));
+}
+
+#[test]
+fn test_to_terminal() {
+    let grid = get_example_grid();
+    let full = Heatmap::default().to_terminal(&grid, 80, 40).unwrap();
+    assert_eq!(10, full.lines().count());
+    assert_eq!("▒ ▒    ▒▒ ", full.lines().next().unwrap());
+    assert_eq!(" ▒▒▓█▒█▓▒▒", full.lines().nth(4).unwrap());
+    let small = Heatmap::default().to_terminal(&grid, 5, 5).unwrap();
+    assert!(small.lines().all(|l| l.chars().count() == 5));
+    assert_eq!(5, small.lines().count());
+
+    let empty = Grid::for_lines(&[], true, crate::Raster::Exact);
+    assert!(matches!(
+        Heatmap::default().to_terminal(&empty, 5, 5),
+        Err(HeatmapError::Empty)
+    ));
+}
+
+#[test]
+fn test_color_maps() {
+    assert_eq!([0, 0, 0], ColorMap::Heat.color(0.0));
+    assert_eq!([255, 255, 255], ColorMap::Heat.color(1.0));
+    assert_eq!([255, 128, 0], ColorMap::Heat.color(0.5));
+    assert_eq!([68, 1, 84], ColorMap::Viridis.color(0.0001));
+}
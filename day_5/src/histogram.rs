@@ -0,0 +1,191 @@
+use std::ops::BitOr;
+
+use crate::{store::Counts, total_len, Bounds, Line, Raster};
+
+#[cfg(test)]
+use crate::Point;
+
+/// The direction of a line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    /// A line along the X axis. Lines that are a single point count as horizontal.
+    Horizontal,
+    /// A line along the Y axis
+    Vertical,
+    /// A line at 45°
+    Diagonal,
+    /// A line at any other angle
+    Arbitrary,
+}
+
+impl Orientation {
+    const ALL: [Orientation; 4] = [
+        Orientation::Horizontal,
+        Orientation::Vertical,
+        Orientation::Diagonal,
+        Orientation::Arbitrary,
+    ];
+}
+
+impl Line {
+    /// Returns the direction of the line
+    pub(crate) fn orientation(&self) -> Orientation {
+        let (dx, dy) = (self.0.x.abs_diff(self.1.x), self.0.y.abs_diff(self.1.y));
+        match (dx, dy) {
+            (_, 0) => Orientation::Horizontal,
+            (0, _) => Orientation::Vertical,
+            _ if dx == dy => Orientation::Diagonal,
+            _ => Orientation::Arbitrary,
+        }
+    }
+}
+
+/// A set of [`Orientation`]s used to pick which lines to count. Sets can be combined with `|`, so
+/// `Orientations::HORIZONTAL | Orientations::DIAGONAL` counts the horizontal and 45° lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Orientations(u8);
+
+impl Orientations {
+    pub const HORIZONTAL: Orientations = Orientations(1);
+    pub const VERTICAL: Orientations = Orientations(1 << 1);
+    pub const DIAGONAL: Orientations = Orientations(1 << 2);
+    pub const ARBITRARY: Orientations = Orientations(1 << 3);
+    /// The lines counted by part #1 of the problem
+    pub const NON_DIAGONAL: Orientations = Orientations(1 | 1 << 1);
+    /// Every line, which is what part #2 of the problem counts as long as there are no lines at
+    /// arbitrary angles
+    pub const ALL: Orientations = Orientations(0b1111);
+
+    /// Returns true if the set includes the orientation
+    pub fn contains(&self, orientation: Orientation) -> bool {
+        self.0 & Orientations::from(orientation).0 != 0
+    }
+}
+
+impl From<Orientation> for Orientations {
+    fn from(orientation: Orientation) -> Self {
+        Orientations(1 << orientation as u8)
+    }
+}
+
+impl BitOr for Orientations {
+    type Output = Orientations;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Orientations(self.0 | rhs.0)
+    }
+}
+
+/// How many points are covered by exactly 1, 2, 3 and more lines, for every combination of line
+/// orientations. [`crate::Grid`] only counts the points that reach 2 overlaps for two fixed sets of
+/// lines, while this answers "how many points have at least `k` overlaps" for any `k` and any
+/// [`Orientations`].
+///
+/// Every line is only walked once. Each point keeps a separate count per orientation, and once all
+/// the lines are in, the counts of every point are added up for each of the 16 possible sets of
+/// orientations at the same time.
+///
+/// ## Example
+/// ```
+/// # use day_5::{parse_input, Orientations, OverlapHistogram};
+/// let histogram = OverlapHistogram::new(&parse_input());
+/// assert_eq!(15463, histogram.at_least(2, Orientations::ALL));
+/// assert_eq!(5698, histogram.at_least(2, Orientations::NON_DIAGONAL));
+/// ```
+#[derive(Clone, Debug)]
+pub struct OverlapHistogram {
+    // Indexed by the bits of the `Orientations`, then by the number of overlapping lines
+    histograms: Vec<Vec<usize>>,
+}
+
+impl OverlapHistogram {
+    /// Counts the overlaps of the lines, using the points picked by [`Raster::Exact`]
+    pub fn new(lines: &[Line]) -> Self {
+        Self::with_raster(lines, Raster::Exact)
+    }
+
+    /// Counts the overlaps of the lines, using the points picked by the [`Raster`]
+    pub fn with_raster(lines: &[Line], raster: Raster) -> Self {
+        let mut counts: Counts<[u32; 4]> = Counts::for_bounds(Bounds::of(lines), total_len(lines));
+        for line in lines {
+            let orientation = line.orientation() as usize;
+            line.for_each_point(raster, |p| counts.update(&p, |c| c[orientation] += 1));
+        }
+
+        let mut histograms = vec![vec![0]; 16];
+        for (_, by_orientation) in counts.iter() {
+            for (set, histogram) in histograms.iter_mut().enumerate().skip(1) {
+                let overlaps = Orientation::ALL
+                    .iter()
+                    .filter(|o| Orientations(set as u8).contains(**o))
+                    .map(|o| by_orientation[*o as usize] as usize)
+                    .sum::<usize>();
+                if overlaps >= histogram.len() {
+                    histogram.resize(overlaps + 1, 0);
+                }
+                histogram[overlaps] += 1;
+            }
+        }
+        // Points no chosen line covers aren't counted
+        histograms.iter_mut().for_each(|h| h[0] = 0);
+        Self { histograms }
+    }
+
+    /// Returns the number of points covered by each number of lines of the chosen orientations. The
+    /// first entry is always 0, the second is the number of points covered by exactly one line and
+    /// so on up to the largest number of overlapping lines.
+    pub fn histogram(&self, orientations: Orientations) -> &[usize] {
+        &self.histograms[orientations.0 as usize]
+    }
+
+    /// Returns the number of points covered by exactly `k` lines of the chosen orientations
+    pub fn exactly(&self, k: usize, orientations: Orientations) -> usize {
+        self.histogram(orientations).get(k).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of points covered by at least `k` lines of the chosen orientations
+    pub fn at_least(&self, k: usize, orientations: Orientations) -> usize {
+        self.histogram(orientations).iter().skip(k.max(1)).sum()
+    }
+}
+
+#[test]
+fn test_orientation() {
+    let line = |x1, y1, x2, y2| Line(Point::from(x1, y1), Point::from(x2, y2));
+    assert_eq!(Orientation::Horizontal, line(0, 9, 5, 9).orientation());
+    assert_eq!(Orientation::Horizontal, line(3, 3, 3, 3).orientation());
+    assert_eq!(Orientation::Vertical, line(7, 0, 7, 4).orientation());
+    assert_eq!(Orientation::Diagonal, line(8, 0, 0, 8).orientation());
+    assert_eq!(Orientation::Arbitrary, line(0, 0, 6, 4).orientation());
+    let set = Orientations::HORIZONTAL | Orientations::ARBITRARY;
+    assert!(set.contains(Orientation::Arbitrary));
+    assert!(!set.contains(Orientation::Vertical));
+}
+
+#[test]
+fn test_overlap_histogram_example() {
+    let histogram = OverlapHistogram::new(&crate::get_example_lines());
+    assert_eq!(&[0, 27, 10, 2], histogram.histogram(Orientations::ALL));
+    assert_eq!(12, histogram.at_least(2, Orientations::ALL));
+    assert_eq!(2, histogram.exactly(3, Orientations::ALL));
+    assert_eq!(0, histogram.exactly(4, Orientations::ALL));
+    assert_eq!(39, histogram.at_least(0, Orientations::ALL));
+    assert_eq!(5, histogram.at_least(2, Orientations::NON_DIAGONAL));
+    // 4,4, 5,5, 6,4 and 5,3 are where the diagonal lines cross or touch
+    assert_eq!(4, histogram.at_least(2, Orientations::DIAGONAL));
+    assert_eq!(0, histogram.at_least(1, Orientations::ARBITRARY));
+}
+
+#[test]
+fn test_overlap_histogram_matches_grid() {
+    for seed in 0..5 {
+        let lines = crate::random_lines(300, 80, seed);
+        let histogram = OverlapHistogram::new(&lines);
+        let (all, non_diagonal) = crate::count_overlapping_points_part_2(lines);
+        assert_eq!(all, histogram.at_least(2, Orientations::ALL));
+        assert_eq!(
+            non_diagonal,
+            histogram.at_least(2, Orientations::NON_DIAGONAL)
+        );
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+
+use crate::{raster::gcd, Line, Point};
+
+/// Lines can only be intersected when their X and Y values are smaller than this (ignoring the sign)
+const COORDINATE_LIMIT: usize = 1 << 62;
+
+/// A line reduced to what's needed to intersect it with other lines: its start, the smallest whole
+/// number step along it, and how many of those steps it takes to reach the end. The points covered
+/// by the line are `start + k * step` for every `k` in `0..=steps`. Everything is an `i128`. With
+/// values below [`COORDINATE_LIMIT`] the steps and the offsets between starts are below 2^63, so the
+/// cross products below stay under 2^127 and can't overflow.
+struct Segment {
+    start: (i128, i128),
+    step: (i128, i128),
+    steps: i128,
+    non_diagonal: bool,
+    min: (i128, i128),
+    max: (i128, i128),
+}
+
+impl Segment {
+    fn from(line: &Line) -> Self {
+        let (a, b) = (line.0, line.1);
+        assert!(
+            [a.x, a.y, b.x, b.y]
+                .iter()
+                .all(|v| v.unsigned_abs() < COORDINATE_LIMIT),
+            "can't intersect lines with values of 2^62 or more"
+        );
+        let steps = gcd(a.x.abs_diff(b.x), a.y.abs_diff(b.y)) as i128;
+        let (dx, dy) = (b.x as i128 - a.x as i128, b.y as i128 - a.y as i128);
+        Segment {
+            start: (a.x as i128, a.y as i128),
+            step: if steps == 0 {
+                (0, 0)
+            } else {
+                (dx / steps, dy / steps)
+            },
+            steps,
+            non_diagonal: !line.diagonal(),
+            min: (a.x.min(b.x) as i128, a.y.min(b.y) as i128),
+            max: (a.x.max(b.x) as i128, a.y.max(b.y) as i128),
+        }
+    }
+
+    fn point(&self, k: i128) -> Point {
+        Point::from(
+            (self.start.0 + k * self.step.0) as isize,
+            (self.start.1 + k * self.step.1) as isize,
+        )
+    }
+
+    /// Returns `k` such that `start + k * step` is the point, if the point is covered by the segment
+    fn position(&self, p: (i128, i128)) -> Option<i128> {
+        let d = (p.0 - self.start.0, p.1 - self.start.1);
+        if self.steps == 0 {
+            return (d == (0, 0)).then_some(0);
+        }
+        self.position_on_line(d)
+            .filter(|k| (0..=self.steps).contains(k))
+    }
+
+    /// Returns the infinite line the segment lies on, or `None` for a segment that's a single point
+    fn line(&self) -> Option<LineKey> {
+        if self.steps == 0 {
+            return None;
+        }
+        // Segments going either way along the same line get the same key
+        let step = if self.step.0 < 0 || (self.step.0 == 0 && self.step.1 < 0) {
+            (-self.step.0, -self.step.1)
+        } else {
+            self.step
+        };
+        Some(LineKey {
+            step,
+            offset: cross(self.start, step),
+        })
+    }
+
+    /// Returns the points covered by both segments, if there are any
+    fn intersect(&self, other: &Segment) -> Option<Shared> {
+        if self.steps == 0 {
+            return other
+                .position(self.start)
+                .map(|_| Shared::Point(self.point(0)));
+        }
+        if other.steps == 0 {
+            return self
+                .position(other.start)
+                .map(|_| Shared::Point(other.point(0)));
+        }
+
+        let w = (other.start.0 - self.start.0, other.start.1 - self.start.1);
+        let denominator = cross(self.step, other.step);
+        if denominator == 0 {
+            // Parallel lines only share points when they're on the same infinite line, in which case
+            // their steps are equal or opposite and the shared points form one run of steps
+            let k = self.position_on_line(w)?;
+            let direction = if other.step == self.step { 1 } else { -1 };
+            let end = k + direction * other.steps;
+            let (lo, hi) = (k.min(end).max(0), k.max(end).min(self.steps));
+            if lo > hi {
+                return None;
+            }
+            let line = self.line().expect("segments with steps lie on a line");
+            let (a, b) = (line.position(self.point(lo)), line.position(self.point(hi)));
+            Some(Shared::Run(line, a.min(b), a.max(b)))
+        } else {
+            // The infinite lines cross at `self.start + u * self.step`, which is a point covered by
+            // both segments only if both `u` and `v` are whole numbers within the segments
+            let u = cross(w, other.step);
+            let v = cross(w, self.step);
+            if u % denominator != 0 || v % denominator != 0 {
+                return None;
+            }
+            let (u, v) = (u / denominator, v / denominator);
+            ((0..=self.steps).contains(&u) && (0..=other.steps).contains(&v))
+                .then(|| Shared::Point(self.point(u)))
+        }
+    }
+
+    /// Returns `k` such that `k * step` equals the offset from the start, regardless of whether it's
+    /// within the segment. Only works for segments with at least one step.
+    fn position_on_line(&self, w: (i128, i128)) -> Option<i128> {
+        let k = if self.step.0 != 0 {
+            w.0 / self.step.0
+        } else {
+            w.1 / self.step.1
+        };
+        (w == (k * self.step.0, k * self.step.1)).then_some(k)
+    }
+}
+
+fn cross(a: (i128, i128), b: (i128, i128)) -> i128 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+/// An infinite line through whole number points, given by the smallest step along it (pointing
+/// right, or down for vertical lines) and which of the parallel lines with that step it is
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+struct LineKey {
+    step: (i128, i128),
+    offset: i128,
+}
+
+impl LineKey {
+    /// Returns where the point lies along the line, which is its X value unless the line is vertical.
+    /// The points of the line are `step` apart, so positions are `stride` apart.
+    fn position(&self, p: Point) -> i128 {
+        if self.step.0 != 0 {
+            p.x as i128
+        } else {
+            p.y as i128
+        }
+    }
+
+    /// Returns how far apart the positions of neighbouring points on the line are
+    fn stride(&self) -> i128 {
+        if self.step.0 != 0 {
+            self.step.0
+        } else {
+            self.step.1
+        }
+    }
+}
+
+/// The points two segments have in common
+#[derive(Clone)]
+enum Shared {
+    /// Crossing segments, or a segment that's a single point, share at most one point
+    Point(Point),
+    /// Segments on the same line share a run of points, from one position along the line to another
+    Run(LineKey, i128, i128),
+}
+
+/// Collects the points shared by pairs of segments and counts each of them once. Runs on the same
+/// line are merged as intervals, rather than storing every point they cover.
+#[derive(Default)]
+struct SharedPoints {
+    // Each point shared by crossing segments, along with the lines of the segments
+    points: HashMap<Point, Vec<LineKey>>,
+    // The runs of shared points on each line
+    runs: HashMap<LineKey, Vec<(i128, i128)>>,
+}
+
+impl SharedPoints {
+    fn add(&mut self, shared: Shared, lines: [Option<LineKey>; 2]) {
+        match shared {
+            Shared::Point(p) => self
+                .points
+                .entry(p)
+                .or_default()
+                .extend(lines.iter().flatten()),
+            Shared::Run(line, from, to) => self.runs.entry(line).or_default().push((from, to)),
+        }
+    }
+
+    /// Returns the number of distinct shared points
+    fn count(mut self) -> usize {
+        let mut total = 0;
+        for (line, runs) in self.runs.iter_mut() {
+            // Merge the runs, which leaves them sorted and apart from each other
+            runs.sort_unstable();
+            let mut merged: Vec<(i128, i128)> = Vec::with_capacity(runs.len());
+            for (from, to) in runs.drain(..) {
+                match merged.last_mut() {
+                    Some(last) if from <= last.1 => last.1 = last.1.max(to),
+                    _ => merged.push((from, to)),
+                }
+            }
+            total += merged
+                .iter()
+                .map(|(from, to)| ((to - from) / line.stride() + 1) as usize)
+                .sum::<usize>();
+            *runs = merged;
+        }
+
+        // A point in a run of shared points on some line is always shared by a segment on that line
+        // and another segment crossing it there, so only the lines of those segments are checked.
+        // The point was already counted once for every line it's in a run on.
+        let in_run = |p: &Point, line: &LineKey| {
+            let runs = self.runs.get(line).map(Vec::as_slice).unwrap_or(&[]);
+            let position = line.position(*p);
+            let i = runs.partition_point(|(from, _)| *from <= position);
+            i > 0 && position <= runs[i - 1].1
+        };
+        for (p, lines) in self.points.iter_mut() {
+            lines.sort_unstable_by_key(|l| (l.step, l.offset));
+            lines.dedup();
+            match lines.iter().filter(|l| in_run(p, l)).count() {
+                0 => total += 1,
+                runs => total -= runs - 1,
+            }
+        }
+        total
+    }
+}
+
+/// Solves part #2 of the problem without walking the lines point by point. Rather than counting how
+/// many lines cover every point, this works out the points that each pair of lines have in common
+/// directly from where the lines are: crossing lines share at most one point, and lines lying on top
+/// of each other share a run of points. Shared ends are just a special case of either. Lines at any
+/// angle are supported, using the same points as [`crate::Raster::Exact`].
+///
+/// The lines are sorted by their smallest X value so that each line is only compared with the lines
+/// whose X range overlaps its own, like a sweep from left to right. Runs of shared points are kept
+/// as intervals along their line, so the cost depends on the number of pairs of lines that are
+/// close and the number of crossings, rather than on how long the lines are. Returns the count for
+/// all lines and for the horizontal and vertical lines, like
+/// [`crate::count_overlapping_points_part_2`]. Panics if any X or Y value is 2^62 or more (ignoring
+/// the sign), since past that the lines' cross products no longer fit in an `i128`.
+pub fn count_overlapping_points_analytic(lines: &[Line]) -> (usize, usize) {
+    let mut segments = lines.iter().map(Segment::from).collect::<Vec<Segment>>();
+    segments.sort_unstable_by_key(|s| s.min.0);
+
+    let mut all = SharedPoints::default();
+    let mut non_diagonal = SharedPoints::default();
+    for (i, a) in segments.iter().enumerate() {
+        for b in segments[i + 1..].iter().take_while(|b| b.min.0 <= a.max.0) {
+            if b.min.1 > a.max.1 || b.max.1 < a.min.1 {
+                continue;
+            }
+            let Some(shared) = a.intersect(b) else {
+                continue;
+            };
+            let lines = [a.line(), b.line()];
+            if a.non_diagonal && b.non_diagonal {
+                non_diagonal.add(shared.clone(), lines);
+            }
+            all.add(shared, lines);
+        }
+    }
+    (all.count(), non_diagonal.count())
+}
+
+#[test]
+fn test_count_overlapping_points_analytic() {
+    let lines = crate::parse_input();
+    assert_eq!((15463, 5698), count_overlapping_points_analytic(&lines));
+    assert_eq!(
+        (12, 5),
+        count_overlapping_points_analytic(&crate::get_example_lines())
+    );
+}
+
+#[test]
+fn test_shared_ends_and_overlaps() {
+    let lines = crate::parse(
+        "0,0 -> 4,0\n4,0 -> 4,4\n4,4 -> 0,0\n2,2 -> 6,6\n6,6 -> 6,6\n9,9 -> 9,9\n9,9 -> 9,9\n0,0 -> 6,4\n9,6 -> 3,2",
+    );
+    // The corners of the triangle, 2,2 to 4,4 along the diagonal, 6,6, 9,9, and 3,2 and 6,4 which
+    // are the only points the last two lines cover between their ends
+    assert_eq!((9, 2), count_overlapping_points_analytic(&lines));
+    assert_eq!(
+        count_overlapping_points_analytic(&lines),
+        crate::count_overlapping_points_part_2(lines)
+    );
+}
+
+#[test]
+fn test_count_overlapping_points_analytic_any_angle() {
+    for seed in 0..10 {
+        let lines = crate::random_lines(200, 60, seed);
+        assert_eq!(
+            crate::count_overlapping_points_part_2(lines.clone()),
+            count_overlapping_points_analytic(&lines)
+        );
+    }
+}
+
+#[test]
+fn test_long_and_crossing_runs() {
+    // Runs on two different lines that share the point 5,5, which is only counted once
+    let lines = crate::parse("5,5 -> 5,1\n5,9 -> 5,3\n9,9 -> 3,3\n6,6 -> 2,2\n0,5 -> 9,5");
+    assert_eq!(
+        crate::count_overlapping_points_part_2(lines.clone()),
+        count_overlapping_points_analytic(&lines)
+    );
+
+    // Runs far too long to walk point by point, crossed in the middle by a third line
+    let lines = crate::parse("0,0 -> 1000000000000,0\n5,0 -> 2000000000000,0\n7,-1 -> 7,1");
+    assert_eq!(
+        (999_999_999_996, 999_999_999_996),
+        count_overlapping_points_analytic(&lines)
+    );
+}
+
+#[test]
+fn test_lines_near_the_coordinate_limit() {
+    // The steps of these lines are nearly 2^63, so their cross products are nearly 2^126
+    let l = (1 << 62) - 1;
+    let lines = vec![
+        Line::new(Point::from(-l, -l), Point::from(l, l - 1)),
+        Line::new(Point::from(l, l - 1), Point::from(-l, -l)),
+        Line::new(Point::from(l, l - 1), Point::from(-l, l)),
+        Line::new(Point::from(-l, l), Point::from(l, 1 - l)),
+    ];
+    assert_eq!((3, 0), count_overlapping_points_analytic(&lines));
+    assert_eq!(
+        crate::count_overlapping_points_part_2(lines.clone()),
+        count_overlapping_points_analytic(&lines)
+    );
+}
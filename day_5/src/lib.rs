@@ -1,112 +1,273 @@
 #![allow(dead_code, unused_variables)]
 
-use itertools::iproduct;
-
-/// When I originally wrote this solution, I used hashmaps to determine if we had lines overlapping
-/// the same points. After loading all of the points into the hashmap, I would iterate over the it,
-/// filtering out any points that had less than two overlapping lines. This turned out to be a lot
-/// slower than I expected (about 45ms) so instead of iterating over the hashmap, I used the hashmap
-/// to determine when a point had been overlapped two times and then incremented a counter. This
-/// effectively removed the lookup iteration at the end, however I was still at about 15-20ms. The
-/// next thing I tried was a highly purpose-built data structure ([`Grid`]) which would use vectors
-/// for point overlap tracking and counters for tracking how often an overlap ocurred. I knew that
-/// there would be a point at which a hashmap would be more effective than a vector, but the benchmarks
-/// showed that at this scale (2d matrix with a width and height of 1000) the performance of a vector
-/// was still faster than the hashmap. I was able to get it down to ~9ms using the vector.
-///
-/// The next optimizations will likely take place in the algorithm that produces points on a line.
+use std::collections::HashMap;
+
+mod counter;
+mod heatmap;
+mod histogram;
+mod intersect;
+mod parse;
+mod query;
+mod raster;
+mod space;
+mod store;
+mod tiled;
+#[cfg(test)]
+mod xorshift;
+
+pub use counter::{Counter, TwoBit};
+pub use heatmap::{ColorMap, Heatmap, HeatmapError};
+pub use histogram::{Orientation, Orientations, OverlapHistogram};
+pub use intersect::count_overlapping_points_analytic;
+pub use parse::{parse_lines, ParseError, ParseErrorKind};
+pub use raster::{LinePoints, Raster};
+pub use space::{count_overlapping_points_3d, parse_3d, Line3, Point3, Space};
+use store::{Bounds, Counts};
+pub use store::{CellValue, Packing};
+pub use tiled::count_overlapping_points_tiled;
+
+// When I originally wrote this solution, I used hashmaps to determine if we had lines overlapping
+// the same points. After loading all of the points into the hashmap, I would iterate over the it,
+// filtering out any points that had less than two overlapping lines. This turned out to be a lot
+// slower than I expected (about 45ms) so instead of iterating over the hashmap, I used the hashmap
+// to determine when a point had been overlapped two times and then incremented a counter. This
+// effectively removed the lookup iteration at the end, however I was still at about 15-20ms. The
+// next thing I tried was a highly purpose-built data structure ([`Grid`]) which would use vectors
+// for point overlap tracking and counters for tracking how often an overlap ocurred. I knew that
+// there would be a point at which a hashmap would be more effective than a vector, but the benchmarks
+// showed that at this scale (2d matrix with a width and height of 1000) the performance of a vector
+// was still faster than the hashmap. I was able to get it down to ~9ms using the vector.
+//
+// The next optimizations will likely take place in the algorithm that produces points on a line.
 
 /// Defines a point using X (0) and Y(1) values
-#[derive(Clone, Hash, Eq, PartialEq)]
-struct Point {
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Point {
     x: isize,
     y: isize,
 }
 
 impl Point {
     /// Creates a new point given an X and Y value
-    fn from(x: isize, y: isize) -> Self {
+    pub fn from(x: isize, y: isize) -> Self {
         Point { x, y }
     }
+
+    /// Returns the X value of the point
+    pub fn x(&self) -> isize {
+        self.x
+    }
+
+    /// Returns the Y value of the point
+    pub fn y(&self) -> isize {
+        self.y
+    }
 }
 
 /// Specifies a line defined by two points
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Line(Point, Point);
 
 impl Line {
-    /// Returns all the whole number points on a single line
-    fn points(&self) -> Vec<Point> {
-        if !self.diagonal() {
-            iproduct!(range(self.0.x, self.1.x), range(self.0.y, self.1.y))
-                .map(|(x, y)| Point::from(x, y))
-                .collect::<Vec<Point>>()
-        } else {
-            range(self.0.x, self.1.x)
-                .into_iter()
-                .zip(range(self.0.y, self.1.y))
-                .map(|(x, y)| Point::from(x, y))
-                .collect()
+    /// Creates a line between the two points
+    pub fn new(a: Point, b: Point) -> Self {
+        Line(a, b)
+    }
+
+    /// Returns all the whole number points on a single line, which works for lines at any angle
+    pub fn points(&self) -> LinePoints {
+        raster::exact(self)
+    }
+
+    /// Calls `f` with each of the points on the line picked by the provided [`Raster`]
+    fn for_each_point(&self, raster: Raster, f: impl FnMut(Point)) {
+        match raster {
+            Raster::Exact => raster::exact(self).for_each(f),
+            Raster::Bresenham => raster::bresenham(self).for_each(f),
         }
     }
 
+    /// Returns the number of whole number points on the line. A line across the whole plane has one
+    /// more point than fits in a usize, so the count stops at `usize::MAX`.
+    fn len(&self) -> usize {
+        raster::gcd(self.0.x.abs_diff(self.1.x), self.0.y.abs_diff(self.1.y)).saturating_add(1)
+    }
+
     /// Returns true if the line is not a horizontal line or a vertical line
     fn diagonal(&self) -> bool {
         self.0.x != self.1.x && self.0.y != self.1.y
     }
 }
 
-struct Grid {
+/// Counts how many lines overlap each point. The grid covers the bounding box of the lines it was
+/// created for, so negative or very large coordinates are fine, see [`Counts`] for how the counts
+/// are stored. Each point is counted with a `usize` unless the grid is created
+/// [`Grid::with_counter`] a smaller [`Counter`].
+pub struct Grid<C: Counter = usize> {
     // Conditionally calculating diagonals was able to save some computational overhead
     // when tackling part #1 of the problem.
     calculate_diagonals: bool,
-    non_diagonal_rows: Vec<Vec<usize>>,
+    raster: Raster,
+    non_diagonal_rows: Counts<C>,
     non_diagonal_counter: usize,
-    all_rows: Vec<Vec<usize>>,
+    all_rows: Counts<C>,
     all_counter: usize,
+    // The lines covering each point, by the order they were pushed in. Only kept when asked for
+    // since it costs a lot more memory than the counts.
+    line_index: Option<HashMap<Point, Vec<usize>>>,
+    lines_pushed: usize,
+    // The lines in the grid by ID, so they can be removed again. Only kept when asked for, since
+    // only grids counting with a usize can remove lines.
+    lines: Option<HashMap<usize, Line>>,
 }
 
 impl Grid {
-    fn with_size(size: usize, calculate_diagonals: bool) -> Self {
-        let mut all_rows = Vec::with_capacity(size);
-        for _ in 0..size {
-            all_rows.push(vec![0; size]);
-        }
+    /// Creates an empty grid sized to hold the provided lines. The overlaps of diagonal lines are
+    /// only counted when `calculate_diagonals` is set.
+    pub fn for_lines(lines: &[Line], calculate_diagonals: bool, raster: Raster) -> Self {
+        Self::with_counter(lines, calculate_diagonals, raster)
+    }
+
+    /// Keeps the lines added from now on, so that [`Grid::remove_line`] can remove them again
+    pub fn with_removable_lines(mut self) -> Self {
+        self.lines.get_or_insert_with(HashMap::new);
+        self
+    }
+
+    /// Removes the line with the ID and returns it, or `None` if the grid doesn't have it. The
+    /// overlaps are updated as if the line had never been added, so points that drop below two
+    /// lines are no longer counted. Returns `None` unless the grid was created
+    /// [`Grid::with_removable_lines`].
+    ///
+    /// Only grids counting with a `usize` can remove lines, since a smaller [`Counter`] that is
+    /// full can't tell how many lines it stopped counting.
+    pub fn remove_line(&mut self, id: usize) -> Option<Line> {
+        let line = self.lines.as_mut()?.remove(&id)?;
+        let diagonal = line.diagonal();
+        line.for_each_point(self.raster, |p| {
+            if let Some(index) = &mut self.line_index {
+                if let Some(ids) = index.get_mut(&p) {
+                    ids.retain(|i| *i != id);
+                    if ids.is_empty() {
+                        index.remove(&p);
+                    }
+                }
+            }
+            if !diagonal && self.non_diagonal_rows.decrement(&p) == 1 {
+                self.non_diagonal_counter -= 1;
+            }
+            if self.calculate_diagonals && self.all_rows.decrement(&p) == 1 {
+                self.all_counter -= 1;
+            }
+        });
+        Some(line)
+    }
+}
+
+impl<C: Counter> Grid<C> {
+    /// Like [`Grid::for_lines`], but counts the lines covering each point with `C`, for example
+    /// `Grid::<u8>::with_counter(&lines, true, Raster::Exact)`
+    pub fn with_counter(lines: &[Line], calculate_diagonals: bool, raster: Raster) -> Self {
+        let non_diagonal = lines.iter().filter(|l| !l.diagonal());
+        let non_diagonal_rows =
+            Counts::for_bounds(Bounds::of(non_diagonal.clone()), total_len(non_diagonal));
+        let all_rows = if calculate_diagonals {
+            Counts::for_bounds(Bounds::of(lines), total_len(lines))
+        } else {
+            Counts::for_bounds(None, 0)
+        };
         Self {
             calculate_diagonals,
-            non_diagonal_rows: all_rows.clone(),
+            raster,
+            non_diagonal_rows,
             non_diagonal_counter: 0,
             all_rows,
             all_counter: 0,
+            line_index: None,
+            lines_pushed: 0,
+            lines: None,
         }
     }
 
+    /// Keeps track of which lines cover each point from now on, see [`Grid::lines_through`]
+    pub fn with_line_index(mut self) -> Self {
+        self.line_index.get_or_insert_with(HashMap::new);
+        self
+    }
+
     /// Pushes a vector of lines into the grid and calculates the appropriate point overlaps for
     /// each line, saving the results into the [`Grid`]'s counters.
-    fn push_lines(&mut self, lines: Vec<Line>) {
-        lines.iter().for_each(|l| {
-            l.points().iter().for_each(|p| {
-                if !l.diagonal() {
-                    self.non_diagonal_rows[p.x as usize][p.y as usize] += 1;
-                    if self.non_diagonal_rows[p.x as usize][p.y as usize] == 2 {
-                        self.non_diagonal_counter += 1;
-                    }
-                }
-                if self.calculate_diagonals {
-                    self.all_rows[p.x as usize][p.y as usize] += 1;
-                    if self.all_rows[p.x as usize][p.y as usize] == 2 {
-                        self.all_counter += 1;
-                    }
-                }
-            })
-        })
+    pub fn push_lines(&mut self, lines: Vec<Line>) {
+        for line in lines {
+            self.add_line(line);
+        }
+    }
+
+    /// Adds a single line to the grid and returns its ID, which [`Grid::remove_line`] takes to
+    /// remove it again. IDs count up from 0 in the order the lines were added.
+    pub fn add_line(&mut self, line: Line) -> usize {
+        let id = self.lines_pushed;
+        self.lines_pushed += 1;
+        let diagonal = line.diagonal();
+        line.for_each_point(self.raster, |p| {
+            if let Some(index) = &mut self.line_index {
+                index.entry(p).or_default().push(id);
+            }
+            if !diagonal && self.non_diagonal_rows.increment(&p) == 2 {
+                self.non_diagonal_counter += 1;
+            }
+            if self.calculate_diagonals && self.all_rows.increment(&p) == 2 {
+                self.all_counter += 1;
+            }
+        });
+        if let Some(lines) = &mut self.lines {
+            lines.insert(id, line);
+        }
+        id
+    }
+
+    /// Returns the line with the ID, if it's in the grid. Returns `None` unless the grid was created
+    /// [`Grid::with_removable_lines`].
+    pub fn line(&self, id: usize) -> Option<&Line> {
+        self.lines.as_ref()?.get(&id)
+    }
+
+    /// Returns the number of points covered by at least two lines, which is the puzzle's answer
+    pub fn overlapping(&self, layer: Layer) -> usize {
+        match layer {
+            Layer::All => self.all_counter,
+            Layer::NonDiagonal => self.non_diagonal_counter,
+        }
+    }
+
+    /// Returns roughly how many bytes the counts of both layers take up
+    pub fn memory_usage(&self) -> usize {
+        self.all_rows.memory_usage() + self.non_diagonal_rows.memory_usage()
+    }
+
+    /// Returns the counts of the layer
+    pub(crate) fn counts(&self, layer: Layer) -> &Counts<C> {
+        match layer {
+            Layer::All => &self.all_rows,
+            Layer::NonDiagonal => &self.non_diagonal_rows,
+        }
     }
 }
 
+/// Picks which of the [`Grid`]'s counts to look at
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Layer {
+    /// The overlaps of all the lines, which are only counted when the grid calculates diagonals
+    #[default]
+    All,
+    /// The overlaps of the horizontal and vertical lines
+    NonDiagonal,
+}
+
 /// Solves part #1 of the problem which asks for the number of overlapping points
 /// from vertical or horizontal lines.
 pub fn count_overlapping_points_part_1(lines: Vec<Line>) -> usize {
-    let mut grid = Grid::with_size(1000, false);
+    let mut grid = Grid::for_lines(&lines, false, Raster::Exact);
     grid.push_lines(lines);
     grid.non_diagonal_counter
 }
@@ -114,50 +275,33 @@ pub fn count_overlapping_points_part_1(lines: Vec<Line>) -> usize {
 /// Solves part #2 of the problem which asks for the number of overlapping points
 /// from horizontal, vertical, or diagonal lines.
 pub fn count_overlapping_points_part_2(lines: Vec<Line>) -> (usize, usize) {
-    let mut grid = Grid::with_size(1000, true);
+    count_overlapping_points_with(lines, Raster::Exact)
+}
+
+/// Like [`count_overlapping_points_part_2`], but also supports lines at any angle. Which points a
+/// line at an angle other than 45° covers depends on the [`Raster`].
+pub fn count_overlapping_points_with(lines: Vec<Line>, raster: Raster) -> (usize, usize) {
+    let mut grid = Grid::for_lines(&lines, true, raster);
     grid.push_lines(lines);
     (grid.all_counter, grid.non_diagonal_counter)
 }
 
-/// Returns a range of numbers between the two provided values. If the first value is higher
-/// then the second value, then we produce a decreasing range.
-fn range(a: isize, b: isize) -> Vec<isize> {
-    if a > b {
-        (b..a + 1).rev().collect::<Vec<isize>>()
-    } else {
-        (a..b + 1).collect::<Vec<isize>>()
-    }
+/// Returns the number of points on all the lines together, stopping at `usize::MAX`
+fn total_len<'a>(lines: impl IntoIterator<Item = &'a Line>) -> usize {
+    lines
+        .into_iter()
+        .fold(0, |total: usize, l| total.saturating_add(l.len()))
 }
 
 /// Parses the input.txt file and returns a vector of lines. This function can panic if the
-/// input does not match the expected format.
+/// input does not match the expected format, use [`parse_lines`] to parse other input.
 pub fn parse_input() -> Vec<Line> {
-    include_str!("input.txt")
-        .lines()
-        .into_iter()
-        .map(|l| {
-            let mut points = l.split("->").into_iter().map(|v| {
-                let mut parts = v.split(",").into_iter();
-                Point {
-                    x: parts
-                        .next()
-                        .expect("expected x")
-                        .trim()
-                        .parse::<isize>()
-                        .expect("expected isize"),
-                    y: parts
-                        .next()
-                        .expect("expected y")
-                        .trim()
-                        .parse::<isize>()
-                        .expect("expected isize"),
-                }
-            });
-            let first = points.next().expect("expected first point");
-            let second = points.next().expect("expected second point");
-            Line(first, second)
-        })
-        .collect::<Vec<Line>>()
+    parse(include_str!("input.txt"))
+}
+
+/// Parses lines in the same format as the input.txt file, panicking on anything else
+fn parse(input: &str) -> Vec<Line> {
+    parse_lines(input.as_bytes()).unwrap_or_else(|e| panic!("invalid input: {}", e))
 }
 
 #[test]
@@ -176,13 +320,8 @@ fn line_points() {
     assert_eq!(5, points.len());
     let points = Line(Point::from(6, 4), Point::from(2, 0)).points();
     assert_eq!(5, points.len());
-}
-
-#[test]
-fn test_range() {
-    assert_eq!(vec![1, 2, 3], range(1, 3));
-    assert_eq!(vec![3, 2, 1], range(3, 1));
-    assert_eq!(vec![9], range(9, 9));
+    let points = Line::new(Point::from(isize::MIN, 0), Point::from(isize::MAX, 1)).points();
+    assert_eq!(2, points.count());
 }
 
 #[test]
@@ -197,3 +336,233 @@ fn test_count_overlapping_points_part_1() {
     let count = count_overlapping_points_part_1(parse_input());
     assert_eq!(5698, count);
 }
+
+#[cfg(test)]
+fn get_example_lines() -> Vec<Line> {
+    parse(
+        "0,9 -> 5,9\n8,0 -> 0,8\n9,4 -> 3,4\n2,2 -> 2,1\n7,0 -> 7,4\n6,4 -> 2,0\n0,9 -> 2,9\n3,4 -> 1,4\n0,0 -> 8,8\n5,5 -> 8,2",
+    )
+}
+
+/// Returns random horizontal, vertical, diagonal and arbitrary lines within a `size` by `size` grid
+#[cfg(test)]
+fn random_lines(count: usize, size: isize, seed: u64) -> Vec<Line> {
+    let mut random = xorshift::XorShift::new(seed);
+    let mut next = move |n: isize| random.below(n);
+    (0..count)
+        .map(|i| {
+            let a = Point::from(next(size), next(size));
+            let b = match i % 4 {
+                0 => Point::from(next(size), a.y),
+                1 => Point::from(a.x, next(size)),
+                2 => {
+                    let d = next(size) - a.x;
+                    Point::from(a.x + d, a.y + d)
+                }
+                _ => Point::from(next(size), next(size)),
+            };
+            Line(a, b)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+fn shift(lines: Vec<Line>, dx: isize, dy: isize) -> Vec<Line> {
+    let shift = |p: Point| Point::from(p.x + dx, p.y + dy);
+    lines
+        .into_iter()
+        .map(|l| Line(shift(l.0), shift(l.1)))
+        .collect()
+}
+
+#[test]
+fn test_count_overlapping_points_example() {
+    assert_eq!(5, count_overlapping_points_part_1(get_example_lines()));
+    assert_eq!(
+        (12, 5),
+        count_overlapping_points_part_2(get_example_lines())
+    );
+}
+
+#[test]
+fn test_count_overlapping_points_negative_and_large() {
+    for (dx, dy) in [
+        (-1000, -1000),
+        (-7, 3),
+        (1 << 40, -(1 << 50)),
+        (isize::MAX - 9, isize::MIN),
+    ] {
+        let lines = shift(get_example_lines(), dx, dy);
+        assert_eq!((12, 5), count_overlapping_points_part_2(lines));
+    }
+}
+
+#[test]
+fn test_count_overlapping_points_sparse() {
+    // The example spread out over a huge area, which ends up backed by a hashmap
+    let mut lines = get_example_lines();
+    lines.extend(shift(get_example_lines(), 1 << 40, 1 << 41));
+    let grid = Grid::for_lines(&lines, true, Raster::Exact);
+    assert!(!grid.all_rows.is_dense());
+    assert_eq!((24, 10), count_overlapping_points_part_2(lines));
+}
+
+#[test]
+fn test_grid_for_lines_across_the_plane() {
+    // Neither the number of points nor the number of cells fits, which must not overflow
+    let lines = [
+        Line(Point::from(isize::MIN, 0), Point::from(isize::MAX, 0)),
+        Line(Point::from(0, isize::MIN), Point::from(0, isize::MAX)),
+    ];
+    assert_eq!(usize::MAX, total_len(&lines));
+    let grid = Grid::for_lines(&lines, true, Raster::Exact);
+    assert!(!grid.all_rows.is_dense());
+}
+
+#[test]
+fn test_count_overlapping_points_any_angle() {
+    // The shallow line only covers 0,0, 3,2 and 6,4 so it meets the horizontal line at 3,2 but
+    // misses the vertical one, while the steep line covers 0,0, 1,3 and 2,6
+    let lines = parse("0,0 -> 6,4\n0,2 -> 6,2\n1,0 -> 1,9\n2,0 -> 9,0\n0,0 -> 2,6");
+    assert_eq!(
+        (4, 1),
+        count_overlapping_points_with(lines.clone(), Raster::Exact)
+    );
+    assert_eq!(
+        (6, 1),
+        count_overlapping_points_with(lines, Raster::Bresenham)
+    );
+}
+
+#[test]
+fn test_compact_counters() {
+    let lines = parse_input();
+    let mut wide = Grid::for_lines(&lines, true, Raster::Exact);
+    let mut byte = Grid::<u8>::with_counter(&lines, true, Raster::Exact);
+    let mut packed = Grid::<TwoBit>::with_counter(&lines, true, Raster::Exact);
+    wide.push_lines(lines.clone());
+    byte.push_lines(lines.clone());
+    packed.push_lines(lines);
+    for layer in [Layer::All, Layer::NonDiagonal] {
+        assert_eq!(wide.overlapping(layer), byte.overlapping(layer));
+        assert_eq!(wide.overlapping(layer), packed.overlapping(layer));
+    }
+    assert_eq!(15335064, wide.memory_usage());
+    assert_eq!(1916883, byte.memory_usage());
+    assert_eq!(479465, packed.memory_usage());
+    assert_eq!(8 * byte.memory_usage(), wide.memory_usage());
+    // The last byte of each row of packed counts can have up to 3 cells to spare, and both layers
+    // each have fewer than 1000 rows
+    assert!((0..3 * 2000).contains(&(4 * packed.memory_usage() - byte.memory_usage())));
+
+    // Counts past what the counter holds read back as full
+    let lines = parse("0,0 -> 0,5\n".repeat(300).as_str());
+    let mut byte = Grid::<u8>::with_counter(&lines, true, Raster::Exact);
+    let mut packed = Grid::<TwoBit>::with_counter(&lines, true, Raster::Exact);
+    byte.push_lines(lines.clone());
+    packed.push_lines(lines);
+    assert_eq!(255, byte.count_at(&Point::from(0, 3), Layer::All));
+    assert_eq!(3, packed.count_at(&Point::from(0, 3), Layer::All));
+    assert_eq!(6, packed.overlapping(Layer::All));
+}
+
+/// Checks that the grid counts the same overlaps as a grid that only ever had the lines
+#[cfg(test)]
+fn assert_same_counts(grid: &Grid, lines: Vec<Line>) {
+    let mut expected = Grid::for_lines(&lines, true, Raster::Exact);
+    for line in lines {
+        expected.add_line(line);
+    }
+    for layer in [Layer::All, Layer::NonDiagonal] {
+        assert_eq!(expected.overlapping(layer), grid.overlapping(layer));
+        assert_eq!(
+            expected.points_with_at_least(1, layer),
+            grid.points_with_at_least(1, layer)
+        );
+    }
+}
+
+#[test]
+fn test_add_and_remove_lines() {
+    let lines = get_example_lines();
+    let mut grid = Grid::for_lines(&lines, true, Raster::Exact)
+        .with_line_index()
+        .with_removable_lines();
+    let ids = lines
+        .iter()
+        .map(|l| grid.add_line(l.clone()))
+        .collect::<Vec<usize>>();
+    assert_eq!((0..10).collect::<Vec<usize>>(), ids);
+    assert_eq!(12, grid.overlapping(Layer::All));
+
+    // Taking out 0,0 -> 8,8 leaves 4,4 with 2 lines, while 2,2 and 5,5 drop to 1
+    let removed = grid.remove_line(8).unwrap();
+    assert_eq!(Point::from(8, 8), removed.1);
+    assert_eq!(10, grid.overlapping(Layer::All));
+    assert_eq!(2, grid.count_at(&Point::from(4, 4), Layer::All));
+    assert_eq!(Some(&[1, 2][..]), grid.lines_through(&Point::from(4, 4)));
+    assert!(grid.remove_line(8).is_none());
+    assert!(grid.line(8).is_none());
+
+    // Removing every horizontal and vertical line leaves the diagonals
+    for id in [0, 2, 3, 4, 6, 7] {
+        grid.remove_line(id);
+    }
+    assert_eq!(0, grid.overlapping(Layer::NonDiagonal));
+    assert!(grid.points_with_at_least(1, Layer::NonDiagonal).is_empty());
+    assert_same_counts(
+        &grid,
+        vec![lines[1].clone(), lines[5].clone(), lines[9].clone()],
+    );
+
+    // Lines added back get new IDs
+    assert_eq!(10, grid.add_line(lines[8].clone()));
+    assert_eq!(Some(&[1, 10][..]), grid.lines_through(&Point::from(4, 4)));
+
+    // Grids only keep their lines when asked to
+    let mut grid = Grid::for_lines(&lines, true, Raster::Exact);
+    grid.push_lines(lines);
+    assert!(grid.line(0).is_none());
+    assert!(grid.remove_line(0).is_none());
+    assert_eq!(12, grid.overlapping(Layer::All));
+}
+
+#[test]
+fn test_remove_lines_outside_bounds() {
+    // The grid is only sized for the example, so the shifted copies spill over into a hashmap
+    let mut grid =
+        Grid::for_lines(&get_example_lines(), true, Raster::Exact).with_removable_lines();
+    grid.push_lines(get_example_lines());
+    let far = shift(get_example_lines(), -(1 << 40), 7);
+    let ids = far
+        .iter()
+        .map(|l| grid.add_line(l.clone()))
+        .collect::<Vec<usize>>();
+    assert_eq!(24, grid.overlapping(Layer::All));
+    for id in ids.iter().step_by(2) {
+        grid.remove_line(*id);
+    }
+    let mut remaining = get_example_lines();
+    remaining.extend(far.into_iter().skip(1).step_by(2));
+    assert_same_counts(&grid, remaining);
+}
+
+#[test]
+fn test_remove_random_lines() {
+    for seed in 0..5 {
+        let lines = random_lines(200, 60, seed);
+        let mut grid = Grid::for_lines(&lines, true, Raster::Exact).with_removable_lines();
+        grid.push_lines(lines.clone());
+        let removed = |id: usize| id % 3 == seed as usize % 3;
+        (0..lines.len()).filter(|id| removed(*id)).for_each(|id| {
+            grid.remove_line(id);
+        });
+        let remaining = lines
+            .into_iter()
+            .enumerate()
+            .filter(|(id, _)| !removed(*id))
+            .map(|(_, l)| l)
+            .collect();
+        assert_same_counts(&grid, remaining);
+    }
+}
@@ -0,0 +1,243 @@
+use std::{error::Error, fmt, io::BufRead, num::ParseIntError, str::FromStr};
+
+use crate::{Line, Line3, Point, Point3};
+
+/// Describes where and why a line of the input could not be parsed. Lines and columns are counted
+/// from 1, and strings parsed on their own with [`FromStr`] are always line 1.
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+/// Describes why a line of the input could not be parsed
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    /// The two points of the line aren't separated by `->`
+    MissingArrow,
+    /// The values of a point aren't separated by a `,`
+    MissingComma,
+    /// The line goes on with another `->` or `,` after its last value
+    TrailingInput,
+    /// A value isn't a whole number that fits in an `isize`
+    InvalidNumber(ParseIntError),
+    /// The input could not be read
+    Io(std::io::Error),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::MissingArrow => write!(f, "expected `->` between the points"),
+            ParseErrorKind::MissingComma => write!(f, "expected `,` between the values"),
+            ParseErrorKind::TrailingInput => write!(f, "unexpected input after the last value"),
+            ParseErrorKind::InvalidNumber(e) => write!(f, "invalid number: {}", e),
+            ParseErrorKind::Io(e) => write!(f, "failed to read input: {}", e),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ParseErrorKind::InvalidNumber(e) => Some(e),
+            ParseErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a point written as `x,y`, with or without spaces around the values
+impl FromStr for Point {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        point(s, 0).map_err(|(offset, kind)| error(s, 1, offset, kind))
+    }
+}
+
+/// Parses a line written as `x1,y1 -> x2,y2`, with or without spaces around the arrow
+impl FromStr for Line {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        line(s).map_err(|(offset, kind)| error(s, 1, offset, kind))
+    }
+}
+
+/// Parses a point in space written as `x,y,z`, with or without spaces around the values
+impl FromStr for Point3 {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        point3(s, 0).map_err(|(offset, kind)| error(s, 1, offset, kind))
+    }
+}
+
+/// Parses a line in space written as `x1,y1,z1 -> x2,y2,z2`, with or without spaces around the arrow
+impl FromStr for Line3 {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (a, b) = ends(s, point3).map_err(|(offset, kind)| error(s, 1, offset, kind))?;
+        Ok(Line3::new(a, b))
+    }
+}
+
+/// Parses every line of the reader into a [`Line`], stopping at the first line that can't be
+/// parsed. Both `\n` and `\r\n` line endings work, and blank lines are skipped.
+///
+/// ## Example
+/// ```
+/// # use day_5::parse_lines;
+/// let lines = parse_lines("0,9 -> 5,9\r\n8,0->0,8\r\n".as_bytes()).unwrap();
+/// assert_eq!(2, lines.len());
+/// let error = parse_lines("0,9 -> 5,9\n8,0 -> 0;8".as_bytes()).unwrap_err();
+/// assert_eq!((2, 11), (error.line, error.column));
+/// ```
+pub fn parse_lines<R: BufRead>(reader: R) -> Result<Vec<Line>, ParseError> {
+    let mut lines = vec![];
+    for (i, text) in reader.lines().enumerate() {
+        let text = text.map_err(|e| ParseError {
+            line: i + 1,
+            column: 1,
+            kind: ParseErrorKind::Io(e),
+        })?;
+        if text.trim().is_empty() {
+            continue;
+        }
+        lines.push(line(&text).map_err(|(offset, kind)| error(&text, i + 1, offset, kind))?);
+    }
+    Ok(lines)
+}
+
+/// An error found while parsing, along with the byte offset into the line it was found at
+type Failure = (usize, ParseErrorKind);
+
+/// Turns a failure into an error pointing at the column of the text it was found at
+fn error(text: &str, line: usize, offset: usize, kind: ParseErrorKind) -> ParseError {
+    ParseError {
+        line,
+        column: text[..offset].chars().count() + 1,
+        kind,
+    }
+}
+
+fn line(s: &str) -> Result<Line, Failure> {
+    let (a, b) = ends(s, point)?;
+    Ok(Line(a, b))
+}
+
+/// Parses the two points on either side of the arrow with `point`
+fn ends<P>(s: &str, point: fn(&str, usize) -> Result<P, Failure>) -> Result<(P, P), Failure> {
+    let (a, b) = s
+        .split_once("->")
+        .ok_or((s.trim_end().len(), ParseErrorKind::MissingArrow))?;
+    if let Some(i) = b.find("->") {
+        return Err((a.len() + 2 + i, ParseErrorKind::TrailingInput));
+    }
+    Ok((point(a, 0)?, point(b, a.len() + 2)?))
+}
+
+/// Parses a point starting `offset` bytes into the line
+fn point(s: &str, offset: usize) -> Result<Point, Failure> {
+    let (x, y) = s
+        .split_once(',')
+        .ok_or((offset + s.trim_end().len(), ParseErrorKind::MissingComma))?;
+    if let Some(i) = y.find(',') {
+        return Err((offset + x.len() + 1 + i, ParseErrorKind::TrailingInput));
+    }
+    Ok(Point::from(
+        number(x, offset)?,
+        number(y, offset + x.len() + 1)?,
+    ))
+}
+
+/// Parses a point in space starting `offset` bytes into the line
+fn point3(s: &str, offset: usize) -> Result<Point3, Failure> {
+    let (x, yz) = s
+        .split_once(',')
+        .ok_or((offset + s.trim_end().len(), ParseErrorKind::MissingComma))?;
+    let p = point(yz, offset + x.len() + 1)?;
+    Ok(Point3::from(number(x, offset)?, p.x, p.y))
+}
+
+/// Parses a number starting `offset` bytes into the line, pointing any error at its first digit
+fn number(s: &str, offset: usize) -> Result<isize, Failure> {
+    let start = s.len() - s.trim_start().len();
+    s.trim()
+        .parse::<isize>()
+        .map_err(|e| (offset + start, ParseErrorKind::InvalidNumber(e)))
+}
+
+#[test]
+fn test_parse_point_and_line() {
+    assert_eq!(Point::from(-3, 40), "-3,40".parse::<Point>().unwrap());
+    assert_eq!(Point::from(7, 1), " 7 , 1 ".parse::<Point>().unwrap());
+    for text in ["0,9 -> 5,9", "0,9->5,9", "0,9  ->5,9\r", " 0, 9 ->  5 ,9 "] {
+        let line = text.parse::<Line>().unwrap();
+        assert_eq!((Point::from(0, 9), Point::from(5, 9)), (line.0, line.1));
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    let fails = |text: &str| {
+        let e = text.parse::<Line>().unwrap_err();
+        (e.line, e.column, e.kind)
+    };
+    assert!(matches!(
+        fails("0,9 5,9"),
+        (1, 8, ParseErrorKind::MissingArrow)
+    ));
+    assert!(matches!(
+        fails("0,9 -> 5;9"),
+        (1, 11, ParseErrorKind::MissingComma)
+    ));
+    assert!(matches!(
+        fails("0,9 ->  x,9"),
+        (1, 9, ParseErrorKind::InvalidNumber(_))
+    ));
+    assert!(matches!(
+        fails("0, -> 5,9"),
+        (1, 4, ParseErrorKind::InvalidNumber(_))
+    ));
+    assert!(matches!(
+        fails("0,99999999999999999999 -> 5,9"),
+        (1, 3, ParseErrorKind::InvalidNumber(_))
+    ));
+    assert!(matches!(
+        fails("0,9 -> 5,9 -> 1,1"),
+        (1, 12, ParseErrorKind::TrailingInput)
+    ));
+    assert!(matches!(
+        fails("0,9 -> 5,9,2"),
+        (1, 11, ParseErrorKind::TrailingInput)
+    ));
+
+    let e = "1,2 -> 3".parse::<Line>().unwrap_err();
+    assert_eq!(
+        "line 1, column 9: expected `,` between the values",
+        e.to_string()
+    );
+    assert!(e.source().is_none());
+}
+
+#[test]
+fn test_parse_lines() {
+    let lines = parse_lines("0,9 -> 5,9\r\n\r\n8,0->0,8\n  \n9,4 ->3,4\n".as_bytes()).unwrap();
+    assert_eq!(3, lines.len());
+    assert_eq!(Point::from(3, 4), lines[2].1);
+
+    let e = parse_lines("0,9 -> 5,9\n\n8,0 -> 0,8\n9,4 -> 3,z".as_bytes()).unwrap_err();
+    assert_eq!((4, 10), (e.line, e.column));
+    assert!(e.source().is_some());
+
+    let e = parse_lines(&[b'1', b',', 0xff, b'\n'][..]).unwrap_err();
+    assert!(matches!(e.kind, ParseErrorKind::Io(_)));
+
+    let input = include_str!("input.txt");
+    assert_eq!(500, parse_lines(input.as_bytes()).unwrap().len());
+}
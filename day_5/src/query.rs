@@ -0,0 +1,163 @@
+use std::cmp::Reverse;
+
+use crate::{Counter, Grid, Layer, Point};
+
+/// Rectangles with up to this many points are searched point by point, anything larger is searched
+/// by going through the covered points instead
+const RECT_SCAN_LIMIT: u128 = 1 << 16;
+
+/// Questions about where the lines are, so that the crowded areas can be avoided. Every query looks
+/// at one [`Layer`] of the grid and lists points sorted by X and then Y. Counters smaller than a
+/// `usize` report the most they can hold for points covered by more lines than that.
+impl<C: Counter> Grid<C> {
+    /// Returns the number of lines covering the point
+    pub fn count_at(&self, point: &Point, layer: Layer) -> usize {
+        self.counts(layer).count(point)
+    }
+
+    /// Returns every point covered by at least `k` lines along with its count
+    pub fn points_with_at_least(&self, k: usize, layer: Layer) -> Vec<(Point, usize)> {
+        let mut points = self
+            .counts(layer)
+            .counts()
+            .filter(|(_, c)| *c >= k.max(1))
+            .collect::<Vec<(Point, usize)>>();
+        points.sort_unstable();
+        points
+    }
+
+    /// Returns the points between `min` and `max` (inclusive) that are covered by at least
+    /// `min_count` lines along with their counts
+    pub fn points_in_rect(
+        &self,
+        min: &Point,
+        max: &Point,
+        min_count: usize,
+        layer: Layer,
+    ) -> Vec<(Point, usize)> {
+        if min.x > max.x || min.y > max.y {
+            return vec![];
+        }
+        let rect = crate::Bounds {
+            min: *min,
+            max: *max,
+        };
+        let (width, height) = rect.size();
+        let counts = self.counts(layer);
+        let scan = width
+            .checked_mul(height)
+            .is_some_and(|points| points <= RECT_SCAN_LIMIT);
+        let mut points = if scan {
+            (min.x..=max.x)
+                .flat_map(|x| (min.y..=max.y).map(move |y| Point::from(x, y)))
+                .map(|p| (p, counts.count(&p)))
+                .filter(|(_, c)| *c >= min_count.max(1))
+                .collect::<Vec<(Point, usize)>>()
+        } else {
+            counts
+                .counts()
+                .filter(|(p, c)| *c >= min_count.max(1) && rect.contains(p))
+                .collect()
+        };
+        points.sort_unstable();
+        points
+    }
+
+    /// Returns the point covered by the most lines along with its count, picking the smallest point
+    /// when there's a tie. Returns `None` when no lines were pushed.
+    pub fn most_dangerous(&self, layer: Layer) -> Option<(Point, usize)> {
+        self.counts(layer)
+            .counts()
+            .max_by_key(|(p, c)| (*c, Reverse(*p)))
+    }
+
+    /// Returns the IDs of the lines covering the point, in the order they were added. Returns `None`
+    /// unless the grid was created [`Grid::with_line_index`].
+    pub fn lines_through(&self, point: &Point) -> Option<&[usize]> {
+        self.line_index
+            .as_ref()
+            .map(|index| index.get(point).map_or(&[][..], |ids| ids.as_slice()))
+    }
+}
+
+#[cfg(test)]
+fn get_example_grid() -> Grid {
+    let lines = crate::get_example_lines();
+    let mut grid = Grid::for_lines(&lines, true, crate::Raster::Exact).with_line_index();
+    grid.push_lines(lines);
+    grid
+}
+
+#[test]
+fn test_count_at() {
+    let grid = get_example_grid();
+    assert_eq!(3, grid.count_at(&Point::from(4, 4), Layer::All));
+    assert_eq!(1, grid.count_at(&Point::from(4, 4), Layer::NonDiagonal));
+    assert_eq!(0, grid.count_at(&Point::from(1, 0), Layer::All));
+    assert_eq!(0, grid.count_at(&Point::from(-100, 5000), Layer::All));
+}
+
+#[test]
+fn test_points_with_at_least() {
+    let grid = get_example_grid();
+    let points = grid.points_with_at_least(3, Layer::All);
+    assert_eq!(vec![(Point::from(4, 4), 3), (Point::from(6, 4), 3)], points);
+    assert_eq!(12, grid.points_with_at_least(2, Layer::All).len());
+    assert_eq!(5, grid.points_with_at_least(2, Layer::NonDiagonal).len());
+    assert_eq!(39, grid.points_with_at_least(0, Layer::All).len());
+}
+
+#[test]
+fn test_points_in_rect() {
+    let grid = get_example_grid();
+    let (min, max) = (Point::from(0, 0), Point::from(2, 2));
+    assert_eq!(
+        vec![(Point::from(2, 2), 2)],
+        grid.points_in_rect(&min, &max, 2, Layer::All)
+    );
+    assert_eq!(5, grid.points_in_rect(&min, &max, 1, Layer::All).len());
+    // Large enough to go through the covered points rather than the rectangle
+    let (min, max) = (Point::from(-1000, -1000), Point::from(1000, 1000));
+    assert_eq!(
+        grid.points_with_at_least(2, Layer::All),
+        grid.points_in_rect(&min, &max, 2, Layer::All)
+    );
+    assert!(grid.points_in_rect(&max, &min, 1, Layer::All).is_empty());
+    // The whole plane, which has more points than fit a u128
+    let (min, max) = (
+        Point::from(isize::MIN, isize::MIN),
+        Point::from(isize::MAX, isize::MAX),
+    );
+    assert_eq!(
+        grid.points_with_at_least(1, Layer::All),
+        grid.points_in_rect(&min, &max, 1, Layer::All)
+    );
+}
+
+#[test]
+fn test_most_dangerous() {
+    let grid = get_example_grid();
+    assert_eq!(
+        Some((Point::from(4, 4), 3)),
+        grid.most_dangerous(Layer::All)
+    );
+    assert_eq!(
+        Some((Point::from(0, 9), 2)),
+        grid.most_dangerous(Layer::NonDiagonal)
+    );
+    let (p, _) = grid.most_dangerous(Layer::All).unwrap();
+    assert_eq!((4, 4), (p.x(), p.y()));
+    let empty = Grid::for_lines(&[], true, crate::Raster::Exact);
+    assert_eq!(None, empty.most_dangerous(Layer::All));
+}
+
+#[test]
+fn test_lines_through() {
+    let grid = get_example_grid();
+    assert_eq!(Some(&[1, 2, 8][..]), grid.lines_through(&Point::from(4, 4)));
+    assert_eq!(Some(&[][..]), grid.lines_through(&Point::from(1, 0)));
+    let lines = crate::get_example_lines();
+    let mut unindexed = Grid::for_lines(&lines, true, crate::Raster::Exact);
+    unindexed.push_lines(lines);
+    assert_eq!(None, unindexed.lines_through(&Point::from(4, 4)));
+}
@@ -0,0 +1,262 @@
+use std::iter::FusedIterator;
+
+use crate::{Line, Point};
+
+/// Decides which points are produced for a line
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Raster {
+    /// Only the points with whole number coordinates that lie exactly on the line. A line from 0,0 to
+    /// 6,4 passes through 0,0, 3,2 and 6,4 and nothing else. For horizontal, vertical and 45° lines
+    /// this is every point between the ends, which is what the puzzle asks for.
+    #[default]
+    Exact,
+    /// Bresenham's algorithm, which picks the nearest point for every step along the longer axis,
+    /// like drawing the line with pixels. Agrees with [`Raster::Exact`] for horizontal, vertical and
+    /// 45° lines.
+    Bresenham,
+}
+
+/// Returns the greatest common divisor of the two numbers, where `gcd(n, 0)` is `n`
+pub(crate) fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Lazily yields the points on a line with whole number coordinates, see [`Raster::Exact`]. The line
+/// is split into `g` equal steps, where `g` is the gcd of the distances travelled along each axis,
+/// which makes every step the smallest whole number move that stays on the line. Nothing is
+/// allocated, and the number of points is known up front, see [`LinePoints::len`].
+///
+/// ## Example
+/// ```
+/// # use day_5::{Line, Point};
+/// let line = Line::new(Point::from(0, 0), Point::from(6, 4));
+/// let points = line.points();
+/// assert_eq!(3, points.len());
+/// assert_eq!(vec![Point::from(0, 0), Point::from(3, 2), Point::from(6, 4)], points.collect::<Vec<Point>>());
+/// ```
+///
+/// A line across the whole plane has 2^64 points, which is one more than a usize can count. That's
+/// why this isn't an [`ExactSizeIterator`], the size hint is only exact while the count fits.
+#[derive(Clone, Debug)]
+pub struct LinePoints {
+    next: Point,
+    // A single step can be longer than an isize, like from isize::MIN,0 straight to isize::MAX,1
+    step: (i128, i128),
+    remaining: u128,
+}
+
+impl LinePoints {
+    /// Returns the number of points left, which is a u128 since it can be 2^64
+    pub fn len(&self) -> u128 {
+        self.remaining
+    }
+
+    /// Returns true once every point has been yielded
+    pub fn is_empty(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+impl Iterator for LinePoints {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let point = self.next;
+        self.remaining -= 1;
+        // Stepping past the end could overflow for lines that end near the edge of an isize
+        if self.remaining > 0 {
+            self.next = Point::from(
+                (point.x as i128 + self.step.0) as isize,
+                (point.y as i128 + self.step.1) as isize,
+            );
+        }
+        Some(point)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match usize::try_from(self.remaining) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+impl FusedIterator for LinePoints {}
+
+/// Returns the points on the line with whole number coordinates
+pub(crate) fn exact(line: &Line) -> LinePoints {
+    let (a, b) = (line.0, line.1);
+    let steps = gcd(a.x.abs_diff(b.x), a.y.abs_diff(b.y)) as i128;
+    // The distance between the ends can be larger than an isize, so work the step out as i128s. A
+    // line that's a single point has no steps, and no step size either.
+    let step = |from: isize, to: isize| (to as i128 - from as i128).checked_div(steps).unwrap_or(0);
+    LinePoints {
+        next: a,
+        step: (step(a.x, b.x), step(a.y, b.y)),
+        remaining: steps as u128 + 1,
+    }
+}
+
+/// Lazily yields the points picked by Bresenham's algorithm, which includes both ends of the line
+/// and has exactly one point for every value along the longer axis.
+#[derive(Clone, Debug)]
+pub(crate) struct BresenhamPoints {
+    // The error terms can be twice the distance between the ends, so work in i128s to be safe
+    x: i128,
+    y: i128,
+    dx: i128,
+    dy: i128,
+    sx: i128,
+    sy: i128,
+    err: i128,
+    remaining: usize,
+}
+
+impl Iterator for BresenhamPoints {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let point = Point::from(self.x as isize, self.y as isize);
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            let e2 = 2 * self.err;
+            if e2 >= self.dy {
+                self.err += self.dy;
+                self.x += self.sx;
+            }
+            if e2 <= self.dx {
+                self.err += self.dx;
+                self.y += self.sy;
+            }
+        }
+        Some(point)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for BresenhamPoints {}
+
+/// Returns the points picked by Bresenham's algorithm
+pub(crate) fn bresenham(line: &Line) -> BresenhamPoints {
+    let (x0, y0) = (line.0.x as i128, line.0.y as i128);
+    let (x1, y1) = (line.1.x as i128, line.1.y as i128);
+    let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+    BresenhamPoints {
+        x: x0,
+        y: y0,
+        dx,
+        dy,
+        sx: (x1 - x0).signum(),
+        sy: (y1 - y0).signum(),
+        err: dx + dy,
+        remaining: dx.max(-dy) as usize + 1,
+    }
+}
+
+#[cfg(test)]
+fn coords(points: impl Iterator<Item = Point>) -> Vec<(isize, isize)> {
+    points.map(|p| (p.x, p.y)).collect()
+}
+
+#[test]
+fn test_gcd() {
+    assert_eq!(4, gcd(8, 12));
+    assert_eq!(7, gcd(0, 7));
+    assert_eq!(1, gcd(9, 4));
+    assert_eq!(0, gcd(0, 0));
+}
+
+#[test]
+fn test_exact_steep_and_shallow() {
+    let steep = Line(Point::from(0, 0), Point::from(2, 6));
+    assert_eq!(vec![(0, 0), (1, 3), (2, 6)], coords(exact(&steep)));
+    let shallow = Line(Point::from(6, 4), Point::from(0, 0));
+    assert_eq!(vec![(6, 4), (3, 2), (0, 0)], coords(exact(&shallow)));
+    let coprime = Line(Point::from(-3, 1), Point::from(4, -4));
+    assert_eq!(vec![(-3, 1), (4, -4)], coords(exact(&coprime)));
+}
+
+#[test]
+fn test_single_point_lines() {
+    let line = Line(Point::from(-5, 5), Point::from(-5, 5));
+    assert_eq!(vec![(-5, 5)], coords(exact(&line)));
+    assert_eq!(vec![(-5, 5)], coords(bresenham(&line)));
+}
+
+#[test]
+fn test_bresenham() {
+    let steep = Line(Point::from(0, 0), Point::from(2, 6));
+    assert_eq!(
+        vec![(0, 0), (0, 1), (1, 2), (1, 3), (1, 4), (2, 5), (2, 6)],
+        coords(bresenham(&steep))
+    );
+    let shallow = Line(Point::from(0, 0), Point::from(6, -4));
+    let points = coords(bresenham(&shallow));
+    assert_eq!(7, points.len());
+    assert_eq!((6, -4), points[6]);
+    assert!(points
+        .windows(2)
+        .all(|w| w[1].0 - w[0].0 == 1 && w[0].1 - w[1].1 <= 1));
+}
+
+#[test]
+fn test_bresenham_matches_exact_on_puzzle_lines() {
+    for line in crate::parse_input() {
+        assert_eq!(coords(exact(&line)), coords(bresenham(&line)));
+    }
+}
+
+#[test]
+fn test_points_exact_size() {
+    let line = Line(Point::from(0, 0), Point::from(9, 6));
+    let mut points = exact(&line);
+    assert_eq!(4, points.len());
+    points.next();
+    assert_eq!(3, points.len());
+    assert_eq!(Some(Point::from(9, 6)), points.last());
+    let mut points = bresenham(&line);
+    assert_eq!(10, points.len());
+    assert_eq!(Some(Point::from(9, 6)), points.nth(9));
+    assert_eq!(None, points.next());
+
+    // Lines ending at the edge of an isize don't overflow
+    let edge = Line(Point::from(isize::MAX - 4, 0), Point::from(isize::MAX, 4));
+    assert_eq!(Some(Point::from(isize::MAX, 4)), exact(&edge).last());
+    assert_eq!(Some(Point::from(isize::MAX, 4)), bresenham(&edge).last());
+}
+
+#[test]
+fn test_exact_across_the_plane() {
+    // A single step from one edge of the plane to the other
+    let line = Line(Point::from(isize::MIN, 0), Point::from(isize::MAX, 1));
+    assert_eq!(2, exact(&line).count());
+    assert_eq!(
+        vec![(isize::MAX, 1), (isize::MIN, 0)],
+        coords(exact(&Line(line.1, line.0)))
+    );
+
+    // 2^64 points, one more than a usize can count
+    let line = Line(Point::from(isize::MAX, 7), Point::from(isize::MIN, 7));
+    let mut points = exact(&line);
+    assert_eq!(1 << 64, points.len());
+    assert_eq!((usize::MAX, None), points.size_hint());
+    assert_eq!(
+        vec![(isize::MAX, 7), (isize::MAX - 1, 7)],
+        coords(points.by_ref().take(2))
+    );
+    assert_eq!((1 << 64) - 2, points.len());
+    assert_eq!((usize::MAX - 1, Some(usize::MAX - 1)), points.size_hint());
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use crate::{raster::gcd, Line, ParseError, Point};
+
+/// Defines a point in space using X, Y and Z values
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Point3 {
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
+}
+
+impl Point3 {
+    /// Creates a new point given an X, Y and Z value
+    pub fn from(x: isize, y: isize, z: isize) -> Self {
+        Point3 { x, y, z }
+    }
+}
+
+/// A point on the plane is a point in space with a Z of 0
+impl From<Point> for Point3 {
+    fn from(p: Point) -> Self {
+        Point3::from(p.x, p.y, 0)
+    }
+}
+
+/// Specifies a line in space defined by two points
+#[derive(Clone, Debug)]
+pub struct Line3(Point3, Point3);
+
+/// A line on the plane is a line in space with a Z of 0
+impl From<Line> for Line3 {
+    fn from(l: Line) -> Self {
+        Line3(l.0.into(), l.1.into())
+    }
+}
+
+impl Line3 {
+    /// Creates a line between the two points
+    pub fn new(a: Point3, b: Point3) -> Self {
+        Line3(a, b)
+    }
+
+    /// Returns all the whole number points on the line. That's every point for lines along an
+    /// axis, along the diagonal of a face of a cube or along the diagonal through the middle of a
+    /// cube, and the evenly spaced points a line at any other angle passes exactly through.
+    pub fn points(&self) -> impl Iterator<Item = Point3> {
+        let (a, b) = (self.0, self.1);
+        let steps = self.steps();
+        // Like the 2D lines the offsets are worked out as i128s, since the difference between two
+        // coordinates can be larger than an isize
+        let step = |from: isize, to: isize| {
+            (to as i128 - from as i128)
+                .checked_div(steps as i128)
+                .unwrap_or(0)
+        };
+        let (dx, dy, dz) = (step(a.x, b.x), step(a.y, b.y), step(a.z, b.z));
+        (0..=steps).map(move |k| {
+            let k = k as i128;
+            Point3::from(
+                (a.x as i128 + k * dx) as isize,
+                (a.y as i128 + k * dy) as isize,
+                (a.z as i128 + k * dz) as isize,
+            )
+        })
+    }
+
+    /// Returns the number of steps between the whole number points of the line, which is one less
+    /// than the number of points and so never overflows
+    fn steps(&self) -> usize {
+        let (a, b) = (self.0, self.1);
+        gcd(gcd(a.x.abs_diff(b.x), a.y.abs_diff(b.y)), a.z.abs_diff(b.z))
+    }
+}
+
+/// Counts how many lines overlap each point in space. Even the puzzle's coordinates would make for
+/// a cube of a billion points, so only the points covered by lines are stored, in a hashmap.
+#[derive(Clone, Debug, Default)]
+pub struct Space {
+    counts: HashMap<Point3, usize>,
+    counter: usize,
+}
+
+impl Space {
+    /// Creates an empty space
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pushes a vector of lines into the space and counts the points where they overlap
+    pub fn push_lines(&mut self, lines: Vec<Line3>) {
+        for line in lines {
+            for p in line.points() {
+                let count = self.counts.entry(p).or_default();
+                *count += 1;
+                if *count == 2 {
+                    self.counter += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns the number of lines covering the point
+    pub fn count_at(&self, point: &Point3) -> usize {
+        self.counts.get(point).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of points covered by at least two lines
+    pub fn overlapping(&self) -> usize {
+        self.counter
+    }
+}
+
+/// Counts the points in space covered by at least two of the lines. Lines on the plane turned into
+/// [`Line3`]s give the same answer as [`crate::count_overlapping_points_part_2`].
+pub fn count_overlapping_points_3d(lines: Vec<Line3>) -> usize {
+    let mut space = Space::new();
+    space.push_lines(lines);
+    space.overlapping()
+}
+
+/// Parses lines in the same format as the input.txt file, but with `x,y,z` points, stopping at the
+/// first line that can't be parsed. Blank lines are skipped.
+///
+/// ## Example
+/// ```
+/// # use day_5::parse_3d;
+/// assert_eq!(2, parse_3d("0,0,0 -> 4,4,4\n6,6,6 -> 2,2,2\n").unwrap().len());
+/// let error = parse_3d("0,0,0 -> 4,4,4\n6,6 -> 2,2,2").unwrap_err();
+/// assert_eq!((2, 4), (error.line, error.column));
+/// ```
+pub fn parse_3d(input: &str) -> Result<Vec<Line3>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| {
+            l.parse::<Line3>()
+                .map_err(|e| ParseError { line: i + 1, ..e })
+        })
+        .collect()
+}
+
+#[test]
+fn test_line3_points() {
+    let points = |input| {
+        parse_3d(input).unwrap()[0]
+            .points()
+            .collect::<Vec<Point3>>()
+    };
+    assert_eq!(4, points("1,2,3 -> 1,2,0").len());
+    assert_eq!(
+        vec![
+            Point3::from(0, 3, 1),
+            Point3::from(1, 2, 1),
+            Point3::from(2, 1, 1)
+        ],
+        points("0,3,1 -> 2,1,1")
+    );
+    assert_eq!(
+        vec![
+            Point3::from(3, 3, 3),
+            Point3::from(2, 2, 4),
+            Point3::from(1, 1, 5)
+        ],
+        points("3,3,3 -> 1,1,5")
+    );
+    // Only 0,0,0, 2,3,5 and 4,6,10 are whole number points
+    assert_eq!(3, points("0,0,0 -> 4,6,10").len());
+    assert_eq!(vec![Point3::from(7, -7, 7)], points("7,-7,7 -> 7,-7,7"));
+    let far = Line3::new(
+        Point3::from(isize::MIN, 0, isize::MAX),
+        Point3::from(isize::MAX, 0, isize::MIN),
+    );
+    assert_eq!(
+        vec![
+            Point3::from(isize::MIN, 0, isize::MAX),
+            Point3::from(isize::MIN + 1, 0, isize::MAX - 1)
+        ],
+        far.points().take(2).collect::<Vec<Point3>>()
+    );
+}
+
+#[test]
+fn test_parse_3d_errors() {
+    use crate::ParseErrorKind;
+
+    let lines = parse_3d("\n-1, 2,3 ->4,5 ,-6\n\n").unwrap();
+    assert_eq!(
+        vec![Point3::from(-1, 2, 3), Point3::from(4, 5, -6)],
+        lines[0]
+            .points()
+            .take(1)
+            .chain(lines[0].points().last())
+            .collect::<Vec<Point3>>()
+    );
+    let fails = |input| {
+        let e = parse_3d(input).unwrap_err();
+        (e.line, e.column, e.kind)
+    };
+    assert!(matches!(
+        fails("1,2,3 -> 4,5"),
+        (1, 13, ParseErrorKind::MissingComma)
+    ));
+    assert!(matches!(
+        fails("1,2,3 -> 4,5,6,7"),
+        (1, 15, ParseErrorKind::TrailingInput)
+    ));
+    assert!(matches!(
+        fails("1,2,3\n"),
+        (1, 6, ParseErrorKind::MissingArrow)
+    ));
+    assert!(matches!(
+        fails("1,2,3 -> 4,5,6\n1,2,z -> 4,5,6"),
+        (2, 5, ParseErrorKind::InvalidNumber(_))
+    ));
+}
+
+#[test]
+fn test_count_overlapping_points_3d() {
+    // An axis line, a face diagonal and a space diagonal all crossing at 2,2,2, plus a face
+    // diagonal on the floor that misses them all
+    let lines = parse_3d("0,2,2 -> 5,2,2\n2,0,0 -> 2,4,4\n0,0,0 -> 4,4,4\n0,1,0 -> 3,4,0").unwrap();
+    let mut space = Space::new();
+    space.push_lines(lines);
+    assert_eq!(1, space.overlapping());
+    assert_eq!(3, space.count_at(&Point3::from(2, 2, 2)));
+    assert_eq!(1, space.count_at(&Point3::from(1, 2, 0)));
+    assert_eq!(0, space.count_at(&Point3::from(1, 1, 0)));
+
+    // The space diagonal overlapping a copy of itself going the other way and further on
+    let lines = parse_3d("0,0,0 -> 4,4,4\n6,6,6 -> 2,2,2").unwrap();
+    assert_eq!(3, count_overlapping_points_3d(lines));
+}
+
+#[test]
+fn test_count_overlapping_points_3d_matches_plane() {
+    let lines = crate::parse_input();
+    let plane = lines
+        .iter()
+        .cloned()
+        .map(Line3::from)
+        .collect::<Vec<Line3>>();
+    assert_eq!(15463, count_overlapping_points_3d(plane.clone()));
+
+    // A copy one level up overlaps with nothing on the plane, while a copy far away still fits
+    let lift = |dz: isize| {
+        plane
+            .iter()
+            .map(|l| Line3(Point3 { z: dz, ..l.0 }, Point3 { z: dz, ..l.1 }))
+            .collect::<Vec<Line3>>()
+    };
+    let mut stacked = lift(0);
+    stacked.extend(lift(1));
+    stacked.extend(lift(1 << 50));
+    assert_eq!(3 * 15463, count_overlapping_points_3d(stacked));
+}
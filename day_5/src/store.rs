@@ -0,0 +1,500 @@
+use std::{any::Any, cell::RefCell, collections::HashMap, mem};
+
+use crate::{Counter, Line, Point};
+
+/// Grids with more cells than this are never backed by a vector, no matter how many points they hold
+const DENSE_LIMIT: u128 = 1 << 24;
+
+/// A grid is backed by a vector as long as it has no more than this many cells per point drawn on
+/// it. Past that, most of the vector would be empty and a hashmap uses less memory.
+const DENSE_RATIO: u128 = 16;
+
+/// How many dropped dense grids' rows are kept around for new grids to reuse, enough for both layers
+/// of a couple of [`crate::Grid`]s
+const SPARE_GRIDS: usize = 4;
+
+/// Dropped grids whose rows take up more bytes than this aren't kept, so that the spare rows never
+/// hold on to more than a few tens of MB
+const SPARE_BYTES: usize = 16 << 20;
+
+thread_local! {
+    /// The rows of dense grids that were dropped, see [`Cells`]
+    static SPARE_ROWS: RefCell<Vec<Box<dyn Any>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The most points a hashmap starts out with room for. Lines can cover far more points than fit in
+/// memory, so the rest are made room for as they're drawn.
+const SPARSE_CAPACITY: usize = 1 << 20;
+
+/// The smallest and largest X and Y values of a set of points, inclusive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Bounds {
+    pub(crate) min: Point,
+    pub(crate) max: Point,
+}
+
+impl Bounds {
+    /// Returns the bounding box of all the line endpoints, or `None` when there are no lines
+    pub(crate) fn of<'a>(lines: impl IntoIterator<Item = &'a Line>) -> Option<Self> {
+        Self::of_points(lines.into_iter().flat_map(|l| [l.0, l.1]))
+    }
+
+    /// Returns the bounding box of the points, or `None` when there are no points
+    pub(crate) fn of_points(points: impl IntoIterator<Item = Point>) -> Option<Self> {
+        points
+            .into_iter()
+            .fold(None, |bounds: Option<Bounds>, p| match bounds {
+                None => Some(Bounds { min: p, max: p }),
+                Some(b) => Some(Bounds {
+                    min: Point::from(b.min.x.min(p.x), b.min.y.min(p.y)),
+                    max: Point::from(b.max.x.max(p.x), b.max.y.max(p.y)),
+                }),
+            })
+    }
+
+    /// The width and height of the box. These can be larger than `isize::MAX` when the box spans
+    /// most of the plane, hence the `u128`s.
+    pub(crate) fn size(&self) -> (u128, u128) {
+        (
+            (self.max.x as i128 - self.min.x as i128 + 1) as u128,
+            (self.max.y as i128 - self.min.y as i128 + 1) as u128,
+        )
+    }
+
+    /// Returns true if the point lies inside the box
+    pub(crate) fn contains(&self, p: &Point) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+}
+
+/// A value kept for each point of a grid. Values small enough to share a byte with others can be
+/// packed together when they're stored in a vector, the rest are stored one after another.
+pub trait CellValue: Copy + Default + PartialEq + 'static {
+    /// How the values are packed when they're stored in a vector, or `None` to store them whole
+    const PACKING: Option<Packing<Self>> = None;
+}
+
+/// Describes how a [`CellValue`] is packed into a few bits of a byte. The default value must be the
+/// one packed as all zero bits.
+#[derive(Clone, Copy, Debug)]
+pub struct Packing<T> {
+    /// How many bits each value takes, which has to divide 8
+    pub bits: u32,
+    /// Returns the value packed into the lowest `bits` bits
+    pub to_bits: fn(T) -> u8,
+    /// Returns the value packed by `to_bits`
+    pub from_bits: fn(u8) -> T,
+}
+
+impl CellValue for usize {}
+impl CellValue for u8 {}
+impl CellValue for [u32; 4] {}
+
+/// The cells of a dense grid, either one value after another or packed a few to a byte. The cells
+/// are kept in a row per X value, like the `rows[x][y]` vectors the grid started out with.
+///
+/// The rows of a dropped grid are kept for the next grid on the same thread to reuse. Without that
+/// the allocator hands the memory back to the OS as soon as a grid is dropped, and every grid pays
+/// for fresh pages all over again, which took more time than drawing the puzzle's lines.
+#[derive(Clone, Debug)]
+pub(crate) enum Cells<T: CellValue> {
+    Whole(Vec<Vec<T>>),
+    Packed {
+        rows: Vec<Vec<u8>>,
+        height: usize,
+        packing: Packing<T>,
+    },
+}
+
+impl<T: CellValue> Cells<T> {
+    /// Creates `width` rows of `height` cells holding the default value
+    fn new(width: usize, height: usize) -> Self {
+        match T::PACKING {
+            Some(packing) => Cells::Packed {
+                rows: spare_rows(width, height.div_ceil((8 / packing.bits) as usize), 0),
+                height,
+                packing,
+            },
+            None => Cells::Whole(spare_rows(width, height, T::default())),
+        }
+    }
+
+    fn width(&self) -> usize {
+        match self {
+            Cells::Whole(rows) => rows.len(),
+            Cells::Packed { rows, .. } => rows.len(),
+        }
+    }
+
+    fn height(&self) -> usize {
+        match self {
+            Cells::Whole(rows) => rows.first().map_or(0, Vec::len),
+            Cells::Packed { height, .. } => *height,
+        }
+    }
+
+    fn get(&self, x: usize, y: usize) -> T {
+        match self {
+            Cells::Whole(rows) => rows[x][y],
+            Cells::Packed { rows, packing, .. } => {
+                let (byte, shift, mask) = packed_position(packing.bits, y);
+                (packing.from_bits)((rows[x][byte] >> shift) & mask)
+            }
+        }
+    }
+
+    fn update(&mut self, x: usize, y: usize, f: impl FnOnce(&mut T)) {
+        match self {
+            Cells::Whole(rows) => f(&mut rows[x][y]),
+            Cells::Packed { rows, packing, .. } => {
+                let (byte, shift, mask) = packed_position(packing.bits, y);
+                let byte = &mut rows[x][byte];
+                let mut value = (packing.from_bits)((*byte >> shift) & mask);
+                f(&mut value);
+                *byte = *byte & !(mask << shift) | ((packing.to_bits)(value) & mask) << shift;
+            }
+        }
+    }
+
+    /// Returns the number of bytes the cells take up
+    fn bytes(&self) -> usize {
+        match self {
+            Cells::Whole(rows) => rows.iter().map(|r| r.capacity() * size_of::<T>()).sum(),
+            Cells::Packed { rows, .. } => rows.iter().map(Vec::capacity).sum(),
+        }
+    }
+}
+
+impl<T: CellValue> Drop for Cells<T> {
+    fn drop(&mut self) {
+        match self {
+            Cells::Whole(rows) => keep_rows(mem::take(rows)),
+            Cells::Packed { rows, .. } => keep_rows(mem::take(rows)),
+        }
+    }
+}
+
+/// Returns `width` rows of `height` values, reusing the rows of a dropped grid if there are any
+fn spare_rows<V: Copy + 'static>(width: usize, height: usize, value: V) -> Vec<Vec<V>> {
+    let spare = SPARE_ROWS.with_borrow_mut(|spare| {
+        let i = spare.iter().position(|rows| rows.is::<Vec<Vec<V>>>())?;
+        spare.swap_remove(i).downcast::<Vec<Vec<V>>>().ok()
+    });
+    let mut rows = spare.map_or_else(Vec::new, |rows| *rows);
+    rows.resize_with(width, Vec::new);
+    for row in rows.iter_mut() {
+        row.clear();
+        row.reserve_exact(height);
+        row.resize(height, value);
+    }
+    rows
+}
+
+/// Keeps the rows of a dropped grid for [`spare_rows`], unless enough rows are kept already
+fn keep_rows<V: 'static>(rows: Vec<Vec<V>>) {
+    // The rows are simply dropped when the thread is going away
+    let _ = SPARE_ROWS.try_with(|spare| {
+        let mut spare = spare.borrow_mut();
+        let bytes = rows
+            .iter()
+            .map(|r| r.capacity() * size_of::<V>())
+            .sum::<usize>();
+        if spare.len() < SPARE_GRIDS && !rows.is_empty() && bytes <= SPARE_BYTES {
+            spare.push(Box::new(rows));
+        }
+    });
+}
+
+/// Returns the byte of its row holding a packed cell, how far the cell is shifted within it and the mask
+/// covering the cell's bits once shifted back down
+fn packed_position(bits: u32, i: usize) -> (usize, u32, u8) {
+    let per_byte = (8 / bits) as usize;
+    (
+        i / per_byte,
+        (i % per_byte) as u32 * bits,
+        (1u16 << bits).wrapping_sub(1) as u8,
+    )
+}
+
+/// Stores the number of lines overlapping each point of a grid. Small or crowded grids are backed by
+/// vectors covering their bounding box, which is what made the original 1000x1000 grid fast.
+/// Grids that are huge or mostly empty (think a handful of lines spread across billions of
+/// coordinates) are backed by a hashmap instead. Either way any point can be counted, points that
+/// land outside of a dense grid's bounding box spill over into a hashmap.
+///
+/// Each point usually holds a single count, but it can hold anything that starts out empty as its
+/// default value, like a count per kind of line.
+#[derive(Clone, Debug)]
+pub(crate) enum Counts<T: CellValue = usize> {
+    Dense {
+        bounds: Bounds,
+        cells: Cells<T>,
+        outside: HashMap<Point, T>,
+    },
+    Sparse(HashMap<Point, T>),
+}
+
+impl<T: CellValue> Counts<T> {
+    /// Picks the backing store for a grid covering `bounds` that will have roughly `points` points
+    /// drawn on it.
+    pub(crate) fn for_bounds(bounds: Option<Bounds>, points: usize) -> Self {
+        let Some(bounds) = bounds else {
+            return Counts::Sparse(HashMap::new());
+        };
+        let (width, height) = bounds.size();
+        match width.checked_mul(height) {
+            Some(cells)
+                if cells <= DENSE_LIMIT && cells <= DENSE_RATIO * (points as u128).max(1 << 16) =>
+            {
+                Counts::Dense {
+                    bounds,
+                    cells: Cells::new(width as usize, height as usize),
+                    outside: HashMap::new(),
+                }
+            }
+            _ => Counts::Sparse(HashMap::with_capacity(points.min(SPARSE_CAPACITY))),
+        }
+    }
+
+    /// Updates the value of the point with `f`
+    pub(crate) fn update(&mut self, p: &Point, f: impl FnOnce(&mut T)) {
+        match self {
+            Counts::Dense {
+                bounds,
+                cells,
+                outside,
+            } => match index(bounds, p) {
+                Some((x, y)) => cells.update(x, y, f),
+                None => f(outside.entry(*p).or_default()),
+            },
+            Counts::Sparse(map) => f(map.entry(*p).or_default()),
+        }
+    }
+
+    /// Returns the value of the point, which is the default for points no lines cover
+    pub(crate) fn get(&self, p: &Point) -> T {
+        match self {
+            Counts::Dense {
+                bounds,
+                cells,
+                outside,
+            } => match index(bounds, p) {
+                Some((x, y)) => cells.get(x, y),
+                None => outside.get(p).copied().unwrap_or_default(),
+            },
+            Counts::Sparse(map) => map.get(p).copied().unwrap_or_default(),
+        }
+    }
+
+    /// Returns every point covered by at least one line along with its value, in no particular order
+    pub(crate) fn iter(&self) -> Box<dyn Iterator<Item = (Point, T)> + '_> {
+        match self {
+            Counts::Dense {
+                bounds,
+                cells,
+                outside,
+            } => {
+                let inside = (0..cells.width())
+                    .flat_map(move |x| (0..cells.height()).map(move |y| (x, y)))
+                    .map(|(x, y)| ((x, y), cells.get(x, y)))
+                    .filter(|(_, c)| *c != T::default())
+                    .map(move |((x, y), c)| {
+                        let x = bounds.min.x + x as isize;
+                        let y = bounds.min.y + y as isize;
+                        (Point::from(x, y), c)
+                    });
+                Box::new(inside.chain(outside.iter().map(|(p, c)| (*p, *c))))
+            }
+            Counts::Sparse(map) => Box::new(map.iter().map(|(p, c)| (*p, *c))),
+        }
+    }
+
+    /// Returns true if the counts are backed by a vector
+    pub(crate) fn is_dense(&self) -> bool {
+        matches!(self, Counts::Dense { .. })
+    }
+
+    /// Returns roughly how many bytes the values take up, counting each hashmap entry as its key
+    /// and value
+    pub(crate) fn memory_usage(&self) -> usize {
+        let entry = size_of::<(Point, T)>();
+        match self {
+            Counts::Dense { cells, outside, .. } => cells.bytes() + outside.capacity() * entry,
+            Counts::Sparse(map) => map.capacity() * entry,
+        }
+    }
+}
+
+impl<C: Counter> Counts<C> {
+    /// Adds one to the count of the point and returns the new count
+    pub(crate) fn increment(&mut self, p: &Point) -> usize {
+        let mut count = 0;
+        self.update(p, |c| {
+            *c = c.incremented();
+            count = c.count();
+        });
+        count
+    }
+
+    /// Returns the number of lines covering the point
+    pub(crate) fn count(&self, p: &Point) -> usize {
+        self.get(p).count()
+    }
+
+    /// Returns every point covered by at least one line along with its count, in no particular
+    /// order
+    pub(crate) fn counts(&self) -> impl Iterator<Item = (Point, usize)> + '_ {
+        self.iter().map(|(p, c)| (p, c.count()))
+    }
+}
+
+impl Counts<usize> {
+    /// Takes one off the count of the point and returns the new count. Points no line covers
+    /// anymore are dropped from the hashmaps, so that [`Counts::iter`] skips them.
+    pub(crate) fn decrement(&mut self, p: &Point) -> usize {
+        let map = match self {
+            Counts::Dense {
+                bounds,
+                cells,
+                outside,
+            } => match index(bounds, p) {
+                Some((x, y)) => {
+                    let mut count = 0;
+                    cells.update(x, y, |c| {
+                        *c -= 1;
+                        count = *c;
+                    });
+                    return count;
+                }
+                None => outside,
+            },
+            Counts::Sparse(map) => map,
+        };
+        let count = map.get_mut(p).expect("only covered points are decremented");
+        *count -= 1;
+        let count = *count;
+        if count == 0 {
+            map.remove(p);
+        }
+        count
+    }
+}
+
+/// Returns the row and the index within the row of the point in a dense grid's cells, or `None` if
+/// it's outside of the grid
+fn index(bounds: &Bounds, p: &Point) -> Option<(usize, usize)> {
+    // Dense grids are small enough for the offsets to fit in a usize. Points before the start of the
+    // grid wrap around to huge offsets, so a single comparison per axis finds the points outside.
+    let (x, y) = (
+        p.x.wrapping_sub(bounds.min.x) as usize,
+        p.y.wrapping_sub(bounds.min.y) as usize,
+    );
+    let (last_x, last_y) = (
+        bounds.max.x.wrapping_sub(bounds.min.x) as usize,
+        bounds.max.y.wrapping_sub(bounds.min.y) as usize,
+    );
+    if x <= last_x && y <= last_y {
+        Some((x, y))
+    } else {
+        None
+    }
+}
+
+#[test]
+fn test_bounds_of() {
+    let lines = [
+        Line(Point::from(3, -4), Point::from(-7, 2)),
+        Line(Point::from(0, 9), Point::from(1, 1)),
+    ];
+    let bounds = Bounds::of(&lines).unwrap();
+    assert_eq!(Point::from(-7, -4), bounds.min);
+    assert_eq!(Point::from(3, 9), bounds.max);
+    assert_eq!((11, 14), bounds.size());
+    assert!(Bounds::of(&[]).is_none());
+
+    let huge = [Line(Point::from(isize::MIN, 0), Point::from(isize::MAX, 0))];
+    assert_eq!((1 << 64, 1), Bounds::of(&huge).unwrap().size());
+}
+
+#[test]
+fn test_counts_backing_store() {
+    let small = Bounds::of(&[Line(Point::from(0, 0), Point::from(999, 999))]);
+    assert!(Counts::<usize>::for_bounds(small, 1000).is_dense());
+    let spread = Bounds::of(&[Line(Point::from(-1 << 40, 0), Point::from(1 << 40, 5))]);
+    assert!(!Counts::<usize>::for_bounds(spread, 1000).is_dense());
+    let plane = Bounds::of(&[Line(
+        Point::from(isize::MIN, isize::MIN),
+        Point::from(isize::MAX, isize::MAX),
+    )]);
+    assert!(!Counts::<usize>::for_bounds(plane, usize::MAX).is_dense());
+
+    // A large grid is only dense when enough points are drawn on it to fill a good part of it
+    let wide = Bounds::of(&[Line(Point::from(0, 0), Point::from(3999, 3999))]);
+    assert!(!Counts::<usize>::for_bounds(wide, 10).is_dense());
+    assert!(Counts::<usize>::for_bounds(wide, 1_000_000).is_dense());
+    let wider = Bounds::of(&[Line(Point::from(0, 0), Point::from(4999, 4999))]);
+    assert!(!Counts::<usize>::for_bounds(wider, usize::MAX).is_dense());
+}
+
+#[test]
+fn test_counts_outside_bounds() {
+    let bounds = Bounds::of(&[Line(Point::from(-2, -2), Point::from(2, 2))]);
+    let mut counts: Counts = Counts::for_bounds(bounds, 5);
+    assert!(counts.is_dense());
+    assert_eq!(1, counts.increment(&Point::from(-2, 2)));
+    assert_eq!(2, counts.increment(&Point::from(-2, 2)));
+    assert_eq!(1, counts.increment(&Point::from(100, -100)));
+    assert_eq!(2, counts.get(&Point::from(-2, 2)));
+    assert_eq!(1, counts.get(&Point::from(100, -100)));
+    assert_eq!(0, counts.get(&Point::from(0, 0)));
+
+    let mut points = counts
+        .iter()
+        .map(|(p, c)| (p.x, p.y, c))
+        .collect::<Vec<_>>();
+    points.sort_unstable();
+    assert_eq!(vec![(-2, 2, 2), (100, -100, 1)], points);
+
+    assert_eq!(1, counts.decrement(&Point::from(-2, 2)));
+    assert_eq!(0, counts.decrement(&Point::from(100, -100)));
+    assert_eq!(0, counts.get(&Point::from(100, -100)));
+    assert_eq!(1, counts.iter().count());
+}
+
+#[test]
+fn test_reused_rows_start_out_empty() {
+    let bounds = Bounds::of(&[Line(Point::from(0, 0), Point::from(9, 9))]);
+    let mut counts: Counts = Counts::for_bounds(bounds, 10);
+    counts.increment(&Point::from(3, 4));
+    drop(counts);
+
+    let bounds = Bounds::of(&[Line(Point::from(0, 0), Point::from(11, 4))]);
+    let counts: Counts = Counts::for_bounds(bounds, 10);
+    assert!(counts.is_dense());
+    assert_eq!(0, counts.iter().count());
+    assert_eq!(0, counts.get(&Point::from(3, 4)));
+    // The reused rows keep their room for 10 values
+    assert_eq!((10 * 10 + 2 * 5) * 8, counts.memory_usage());
+}
+
+#[test]
+fn test_packed_cells() {
+    use crate::TwoBit;
+
+    let mut cells: Cells<TwoBit> = Cells::new(2, 9);
+    assert_eq!(6, cells.bytes());
+    for i in 0..9 {
+        (0..i % 5).for_each(|_| cells.update(1, i, |c| *c = c.incremented()));
+    }
+    let counts = (0..9)
+        .map(|i| cells.get(1, i).count())
+        .collect::<Vec<usize>>();
+    assert_eq!(vec![0, 1, 2, 3, 3, 0, 1, 2, 3], counts);
+    assert!((0..9).all(|i| cells.get(0, i) == TwoBit::default()));
+
+    let bounds = Bounds::of(&[Line(Point::from(0, 0), Point::from(99, 99))]);
+    let packed: Counts<TwoBit> = Counts::for_bounds(bounds, 100);
+    let whole: Counts<usize> = Counts::for_bounds(bounds, 100);
+    assert_eq!(2500, packed.memory_usage());
+    assert_eq!(80000, whole.memory_usage());
+}
@@ -0,0 +1,20 @@
+//! A xorshift generator, which is plenty random for tests and benchmarks and saves a dependency.
+//! The benchmarks include this file with `#[path]`, so it can't depend on the rest of the crate.
+
+/// Generates pseudo-random numbers from a seed, the same numbers for the same seed every time
+pub struct XorShift(u64);
+
+impl XorShift {
+    /// Creates a generator from the seed, any seed works including 0
+    pub fn new(seed: u64) -> Self {
+        XorShift(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    /// Returns a number from `0..n`
+    pub fn below(&mut self, n: isize) -> isize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as isize
+    }
+}
@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use day_6::{num_laternfish, parse_input};
+use day_6::{num_laternfish, num_laternfish_exact, num_laternfish_modulo, parse_input};
 
 fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("num_laternfish_part_1", |b| {
@@ -14,6 +14,27 @@ fn criterion_benchmark(c: &mut Criterion) {
             num_laternfish(input.clone(), 256);
         })
     });
+    // The matrix takes 144 us for part #2 against 3.9 us for stepping through the days, since it
+    // multiplies big integers. It pays off past what a usize can hold: 100000 days took 16.0 ms
+    // exactly, and usize::MAX days took 711 us modulo 1000000007.
+    c.bench_function("num_laternfish_exact_part_2", |b| {
+        let input = parse_input();
+        b.iter(|| {
+            num_laternfish_exact(&input, 256);
+        })
+    });
+    c.bench_function("num_laternfish_exact_100000_days", |b| {
+        let input = parse_input();
+        b.iter(|| {
+            num_laternfish_exact(&input, 100_000);
+        })
+    });
+    c.bench_function("num_laternfish_modulo_max_days", |b| {
+        let input = parse_input();
+        b.iter(|| {
+            num_laternfish_modulo(&input, usize::MAX, 1_000_000_007);
+        })
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -1,5 +1,9 @@
 #![allow(dead_code, unused_variables)]
 
+mod matrix;
+
+pub use matrix::{num_laternfish_exact, num_laternfish_modulo};
+
 /// Returns the number of laternfish that would be alive after a number of days, given the initial
 /// laternfish's ages.
 pub fn num_laternfish(initial: Vec<usize>, days: usize) -> usize {
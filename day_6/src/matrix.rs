@@ -0,0 +1,196 @@
+use num_bigint::BigUint;
+
+/// The numbers the fish are counted with, which are either exact or taken modulo some number
+trait Arithmetic {
+    type Value: Clone;
+
+    fn from(&self, n: usize) -> Self::Value;
+    fn add(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+    fn mul(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+/// Counts exactly, however large the count gets
+struct Exact;
+
+impl Arithmetic for Exact {
+    type Value = BigUint;
+
+    fn from(&self, n: usize) -> BigUint {
+        BigUint::from(n)
+    }
+
+    fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a + b
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a * b
+    }
+}
+
+/// Counts modulo a number. The products are worked out as `u128`s so they can't overflow.
+struct Modulo(u64);
+
+impl Arithmetic for Modulo {
+    type Value = u64;
+
+    fn from(&self, n: usize) -> u64 {
+        (n as u128 % self.0 as u128) as u64
+    }
+
+    fn add(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 + *b as u128) % self.0 as u128) as u64
+    }
+
+    fn mul(&self, a: &u64, b: &u64) -> u64 {
+        (*a as u128 * *b as u128 % self.0 as u128) as u64
+    }
+}
+
+/// A 9x9 matrix, where multiplying the number of fish with each timer by `matrix[i][j]` gives how
+/// many fish with timer `i` the fish with timer `j` turn into
+type Matrix<T> = [[T; 9]; 9];
+
+/// Returns the matrix for a single day. Every timer goes down by one, and the fish at 0 go back to
+/// 6 along with a new fish at 8.
+fn one_day<A: Arithmetic>(arithmetic: &A) -> Matrix<A::Value> {
+    std::array::from_fn(|i| {
+        std::array::from_fn(|j| {
+            let becomes = j == i + 1 || (j == 0 && (i == 6 || i == 8));
+            arithmetic.from(becomes as usize)
+        })
+    })
+}
+
+fn multiply<A: Arithmetic>(
+    arithmetic: &A,
+    a: &Matrix<A::Value>,
+    b: &Matrix<A::Value>,
+) -> Matrix<A::Value> {
+    std::array::from_fn(|i| {
+        std::array::from_fn(|j| {
+            (1..9).fold(arithmetic.mul(&a[i][0], &b[0][j]), |sum, k| {
+                arithmetic.add(&sum, &arithmetic.mul(&a[i][k], &b[k][j]))
+            })
+        })
+    })
+}
+
+/// Counts the fish after a number of days by raising the matrix of a single day to the power of
+/// `days`, squaring it once per bit of `days`
+fn count<A: Arithmetic>(arithmetic: &A, initial: &[usize], days: usize) -> A::Value {
+    let mut result: Matrix<A::Value> =
+        std::array::from_fn(|i| std::array::from_fn(|j| arithmetic.from((i == j) as usize)));
+    let mut power = one_day(arithmetic);
+    let mut remaining = days;
+    while remaining > 0 {
+        if remaining & 1 == 1 {
+            result = multiply(arithmetic, &result, &power);
+        }
+        remaining >>= 1;
+        if remaining > 0 {
+            power = multiply(arithmetic, &power, &power);
+        }
+    }
+
+    let mut timers = [0; 9];
+    initial.iter().for_each(|t| timers[*t] += 1);
+    let mut total = arithmetic.from(0);
+    for row in result.iter() {
+        for (cell, fish) in row.iter().zip(timers) {
+            total = arithmetic.add(&total, &arithmetic.mul(cell, &arithmetic.from(fish)));
+        }
+    }
+    total
+}
+
+/// Returns the exact number of laternfish that would be alive after a number of days, given the
+/// initial laternfish's ages. Unlike [`crate::num_laternfish`] this never overflows and only takes
+/// O(log days) steps, since it raises the 9x9 matrix of a single day to the power of `days` rather
+/// than stepping through each day. The count still grows by about 9% a day though, so a million
+/// days makes for a number with nearly 38000 digits.
+///
+/// ## Example
+/// ```
+/// # use day_6::num_laternfish_exact;
+/// let fish = num_laternfish_exact(&[3, 4, 3, 1, 2], 256);
+/// assert_eq!("26984457539", fish.to_string());
+/// let fish = num_laternfish_exact(&[3, 4, 3, 1, 2], 1000);
+/// assert!(fish.to_string().starts_with("37958906114469825913"));
+/// ```
+pub fn num_laternfish_exact(initial: &[usize], days: usize) -> BigUint {
+    count(&Exact, initial, days)
+}
+
+/// Returns the number of laternfish that would be alive after a number of days modulo `prime`, which
+/// only needs 64 bit numbers however many days go by. Panics if `prime` is 0.
+pub fn num_laternfish_modulo(initial: &[usize], days: usize, prime: u64) -> u64 {
+    assert!(prime > 0, "can't count modulo 0");
+    count(&Modulo(prime), initial, days)
+}
+
+/// Steps through the days one fish at a time, which is slow but obviously right
+#[cfg(test)]
+fn simulate(initial: &[usize], days: usize) -> usize {
+    let mut fish = initial.to_vec();
+    for _ in 0..days {
+        let born = fish.iter().filter(|t| **t == 0).count();
+        fish.iter_mut()
+            .for_each(|t| *t = if *t == 0 { 6 } else { *t - 1 });
+        fish.extend(std::iter::repeat_n(8, born));
+    }
+    fish.len()
+}
+
+#[test]
+fn test_matches_num_laternfish() {
+    for initial in [vec![3, 4, 3, 1, 2], crate::parse_input()] {
+        // Every day until the count no longer fits in a usize
+        let mut days = 0;
+        loop {
+            let exact = num_laternfish_exact(&initial, days);
+            if exact > BigUint::from(usize::MAX) {
+                break;
+            }
+            assert_eq!(
+                BigUint::from(crate::num_laternfish(initial.clone(), days)),
+                exact
+            );
+            days += 1;
+        }
+        assert!(days > 400);
+    }
+    assert_eq!(
+        BigUint::from(1572643095893usize),
+        num_laternfish_exact(&crate::parse_input(), 256)
+    );
+}
+
+#[test]
+fn test_matches_simulation() {
+    // Includes fish that are about to give birth on the very first day
+    let initial = [0, 8, 6, 0, 3];
+    for days in 0..60 {
+        assert_eq!(
+            BigUint::from(simulate(&initial, days)),
+            num_laternfish_exact(&initial, days)
+        );
+    }
+}
+
+#[test]
+fn test_num_laternfish_modulo() {
+    let input = crate::parse_input();
+    for prime in [2, 7, 1_000_000_007, 18_446_744_073_709_551_557] {
+        for days in [0, 1, 80, 256, 1000, 5000] {
+            let exact = num_laternfish_exact(&input, days) % BigUint::from(prime);
+            assert_eq!(
+                exact,
+                BigUint::from(num_laternfish_modulo(&input, days, prime))
+            );
+        }
+    }
+    // Far too many days to count exactly
+    assert!(num_laternfish_modulo(&input, usize::MAX, 1_000_000_007) < 1_000_000_007);
+    assert_eq!(0, num_laternfish_modulo(&input, 1 << 40, 1));
+}